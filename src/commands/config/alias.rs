@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use crate::{
+    config::servers::ServerConfig,
+    parser::{command, Rest, TextCommand},
+    storage::Storage,
+};
+
+command! {
+    /// Manage this server's command aliases.
+    #[category = Config]
+    #[guild_only = true]
+    pub group alias { add, remove, list }
+}
+
+command! {
+    /// Make a name run a command, optionally with some arguments.
+    #[category = Config]
+    #[permissions = MANAGE_GUILD]
+    pub async fn add(s: State, ctx: Context, name: String, command: Rest) -> anyhow::Result<()> {
+        let expansion = command.0;
+        // Stored lowercase like command names, so they can be matched regardless of case.
+        let name = name.to_lowercase();
+
+        if s.root_cmd.find_command(&name).is_some() {
+            return ctx
+                .reply(&format!(
+                    "`{name}` is already a command, pick another name for the alias."
+                ))
+                .await;
+        }
+
+        let guild = ctx.guild()?;
+        let _saving = s.lock_config(guild).await;
+        let mut configs = s.server_configs.lock().await;
+        let config = configs.entry(guild).or_insert_with(ServerConfig::new);
+
+        let pfx = config.prefix(&s.bot.default_prefix);
+        let expansion = String::from(expansion.strip_prefix(&pfx).unwrap_or(&expansion));
+        let target = TextCommand::new(&expansion).next().unwrap_or_default();
+        if s.root_cmd.find_command(&target).is_none() {
+            drop(configs);
+            return ctx
+                .reply(&format!(
+                    "`{target}` is not a command, so `{name}` would not do anything."
+                ))
+                .await;
+        }
+
+        config
+            .aliases
+            .get_or_insert_with(HashMap::new)
+            .insert(name.clone(), expansion.clone());
+        let config = config.clone();
+        drop(configs);
+
+        s.storage.save_config(guild, &config).await?;
+        ctx.reply(&format!("`{pfx}{name}` now runs `{pfx}{expansion}`."))
+            .await
+    }
+}
+
+command! {
+    /// Remove an alias.
+    #[category = Config]
+    #[permissions = MANAGE_GUILD]
+    pub async fn remove(s: State, ctx: Context, name: String) -> anyhow::Result<()> {
+        let name = name.to_lowercase();
+        let guild = ctx.guild()?;
+        let _saving = s.lock_config(guild).await;
+        let removed = s
+            .server_configs
+            .lock()
+            .await
+            .get_mut(&guild)
+            .and_then(|config| {
+                let aliases = config.aliases.as_mut()?;
+                // Aliases added before names were stored lowercase may not be.
+                let key = aliases.keys().find(|n| n.to_lowercase() == name)?.clone();
+                aliases.remove(&key);
+                Some(config.clone())
+            });
+
+        let Some(config) = removed else {
+            return ctx
+                .reply(&format!("There is no alias called `{name}`."))
+                .await;
+        };
+
+        s.storage.save_config(guild, &config).await?;
+        ctx.reply(&format!("Removed the alias `{name}`.")).await
+    }
+}
+
+command! {
+    /// List this server's aliases.
+    #[category = Config]
+    pub async fn list(s: State, ctx: Context) -> anyhow::Result<()> {
+        let config = s.config(Some(ctx.guild()?)).await;
+        let pfx = config.prefix(&s.bot.default_prefix);
+
+        let mut aliases = config
+            .aliases
+            .iter()
+            .flatten()
+            .map(|(name, expansion)| format!("{pfx}{name} -> {pfx}{expansion}"))
+            .collect::<Vec<_>>();
+
+        let content = if aliases.is_empty() {
+            String::from("This server has no aliases.")
+        } else {
+            aliases.sort_unstable();
+            format!("Aliases\n```\n{}\n```", aliases.join("\n"))
+        };
+        ctx.reply(&content).await
+    }
+}
@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+
+use twilight_model::channel::message::EmojiReactionType;
+
+use super::{check_emoji, update};
+use crate::parser::command;
+
+command! {
+    /// Manage the emoji the bot reacts to messages with automatically.
+    #[category = Config]
+    pub group autoreact { add, remove }
+}
+
+command! {
+    /// React to every message containing a trigger with one of this server's emoji.
+    #[category = Config]
+    pub async fn add(
+        s: State,
+        ctx: Context,
+        trigger: String,
+        emoji: EmojiReactionType,
+    ) -> anyhow::Result<()> {
+        let guild = ctx.guild()?;
+        let emoji = check_emoji(&s, guild, &emoji)?;
+        let trigger = trigger.to_lowercase();
+        let content = update(&s, guild, |config| {
+            config
+                .auto_reacts
+                .get_or_insert_with(HashMap::new)
+                .insert(trigger.clone(), emoji);
+            format!("Reacting to `{trigger}` with <:_:{emoji}>.")
+        })
+        .await?;
+        ctx.reply(&content).await
+    }
+}
+
+command! {
+    /// Stop reacting to a trigger.
+    #[category = Config]
+    pub async fn remove(s: State, ctx: Context, trigger: String) -> anyhow::Result<()> {
+        let trigger = trigger.to_lowercase();
+        let content = update(&s, ctx.guild()?, |config| {
+            let removed = config
+                .auto_reacts
+                .as_mut()
+                .and_then(|reacts| reacts.remove(&trigger));
+            if config.auto_reacts.as_ref().is_some_and(HashMap::is_empty) {
+                config.auto_reacts = None;
+            }
+            match removed {
+                Some(_) => format!("Stopped reacting to `{trigger}`."),
+                None => format!("There is no reaction to `{trigger}`."),
+            }
+        })
+        .await?;
+        ctx.reply(&content).await
+    }
+}
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use super::update;
+use crate::parser::{command, Rest};
+
+command! {
+    /// Manage the messages the bot answers automatically.
+    #[category = Config]
+    pub group autoresponse { add, remove }
+}
+
+command! {
+    /// Answer every message containing a trigger with a response.
+    #[category = Config]
+    pub async fn add(
+        s: State,
+        ctx: Context,
+        trigger: String,
+        response: Rest,
+    ) -> anyhow::Result<()> {
+        let trigger = trigger.to_lowercase();
+        let content = update(&s, ctx.guild()?, |config| {
+            let responses = config.auto_responses.get_or_insert_with(HashMap::new);
+            match responses.insert(trigger.clone(), response.0) {
+                Some(_) => format!("Replaced the response to `{trigger}`."),
+                None => format!("Added a response to `{trigger}`."),
+            }
+        })
+        .await?;
+        ctx.reply(&content).await
+    }
+}
+
+command! {
+    /// Stop answering a trigger.
+    #[category = Config]
+    pub async fn remove(s: State, ctx: Context, trigger: String) -> anyhow::Result<()> {
+        let trigger = trigger.to_lowercase();
+        let content = update(&s, ctx.guild()?, |config| {
+            let removed = config
+                .auto_responses
+                .as_mut()
+                .and_then(|responses| responses.remove(&trigger));
+            if config.auto_responses.as_ref().is_some_and(HashMap::is_empty) {
+                config.auto_responses = None;
+            }
+            match removed {
+                Some(_) => format!("Removed the response to `{trigger}`."),
+                None => format!("There is no response to `{trigger}`."),
+            }
+        })
+        .await?;
+        ctx.reply(&content).await
+    }
+}
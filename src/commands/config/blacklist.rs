@@ -0,0 +1,51 @@
+use super::update;
+use crate::parser::command;
+
+command! {
+    /// Manage the words which are not allowed in this server.
+    #[category = Config]
+    pub group blacklist { add, remove }
+}
+
+command! {
+    /// Stop a word from being used in this server.
+    #[category = Config]
+    pub async fn add(s: State, ctx: Context, word: String) -> anyhow::Result<()> {
+        let word = word.to_lowercase();
+        let content = update(&s, ctx.guild()?, |config| {
+            let mut words = config.blacklisted_words.take().unwrap_or_default().into_vec();
+            let content = if words.contains(&word) {
+                format!("`{word}` is already blacklisted.")
+            } else {
+                words.push(word.clone());
+                format!("Blacklisted `{word}`.")
+            };
+            config.blacklisted_words = Some(words.into());
+            content
+        })
+        .await?;
+        ctx.reply(&content).await
+    }
+}
+
+command! {
+    /// Allow a blacklisted word again.
+    #[category = Config]
+    pub async fn remove(s: State, ctx: Context, word: String) -> anyhow::Result<()> {
+        let word = word.to_lowercase();
+        let content = update(&s, ctx.guild()?, |config| {
+            let mut words = config.blacklisted_words.take().unwrap_or_default().into_vec();
+            let before = words.len();
+            words.retain(|w| *w != word);
+            let content = if words.len() == before {
+                format!("`{word}` is not blacklisted.")
+            } else {
+                format!("`{word}` is allowed again.")
+            };
+            config.blacklisted_words = (!words.is_empty()).then(|| words.into());
+            content
+        })
+        .await?;
+        ctx.reply(&content).await
+    }
+}
@@ -1,3 +1,169 @@
+mod alias;
+mod autoreact;
+mod autoresponse;
+mod blacklist;
 mod prefix;
+mod reactionrole;
+mod rules;
+mod settings;
+mod significant;
+mod suggestions;
+mod transfer;
 
-pub use prefix::prefix;
+use twilight_model::{
+    channel::message::EmojiReactionType,
+    id::{
+        marker::{ChannelMarker, EmojiMarker, GuildMarker, MessageMarker, RoleMarker},
+        Id,
+    },
+};
+
+use crate::{
+    config::servers::ServerConfig, error::CommandError, parser::Command, storage::Storage, State,
+};
+
+/// The top level commands defined here, in the order `help` lists them.
+pub fn top_level() -> Vec<Command> {
+    vec![
+        prefix::prefix(),
+        alias::alias(),
+        suggestions::suggestions(),
+        rules::commands(),
+        settings::config(),
+    ]
+}
+
+/// Applies `change` to this guild's config and saves it, returning the message to reply with.
+async fn update(
+    s: &State,
+    guild: Id<GuildMarker>,
+    change: impl FnOnce(&mut ServerConfig) -> String,
+) -> anyhow::Result<String> {
+    let _saving = s.lock_config(guild).await;
+    let mut configs = s.server_configs.lock().await;
+    let config = configs.entry(guild).or_insert_with(ServerConfig::new);
+    let content = change(config);
+    let config = config.clone();
+    drop(configs);
+
+    s.storage.save_config(guild, &config).await?;
+    Ok(content)
+}
+
+/// Fails unless `channel` is one of `guild`'s channels.
+fn check_channel(
+    s: &State,
+    guild: Id<GuildMarker>,
+    channel: Id<ChannelMarker>,
+) -> Result<(), CommandError> {
+    s.cache
+        .channel(channel)
+        .filter(|c| c.guild_id == Some(guild))
+        .map(|_| ())
+        .ok_or(CommandError::UnknownChannel)
+}
+
+/// Fails unless `role` is one of `guild`'s roles which the bot can give out, i.e. one below its
+/// highest role.
+async fn check_role(
+    s: &State,
+    guild: Id<GuildMarker>,
+    role: Id<RoleMarker>,
+) -> Result<(), CommandError> {
+    check_role_below(s, guild, role, highest_role(s, guild).await?)
+}
+
+/// Fails unless `role` is one of `guild`'s roles below the position `highest`.
+fn check_role_below(
+    s: &State,
+    guild: Id<GuildMarker>,
+    role: Id<RoleMarker>,
+    highest: i64,
+) -> Result<(), CommandError> {
+    // `@everyone` shares its id with the guild.
+    if role.cast::<GuildMarker>() == guild {
+        return Err(CommandError::UnknownRole);
+    }
+    let position = s
+        .cache
+        .role(role)
+        .filter(|r| r.guild_id() == guild)
+        .map(|r| r.position)
+        .ok_or(CommandError::UnknownRole)?;
+    if position >= highest {
+        return Err(CommandError::RoleTooHigh);
+    }
+    Ok(())
+}
+
+/// The position of the bot's highest role in `guild`, asking Discord for the bot's roles when
+/// they are not cached.
+async fn highest_role(s: &State, guild: Id<GuildMarker>) -> Result<i64, CommandError> {
+    let me = match s.cache.current_user() {
+        Some(me) => me.id,
+        None => fetch_own(s.http.current_user().await).await?.id,
+    };
+    let roles = match s.cache.member(guild, me) {
+        Some(member) => member.roles().to_vec(),
+        None => fetch_own(s.http.guild_member(guild, me).await).await?.roles,
+    };
+    Ok(roles
+        .iter()
+        .filter_map(|r| s.cache.role(*r).map(|r| r.position))
+        .max()
+        .unwrap_or(0))
+}
+
+/// The model of a `response` about the bot itself, logging why it could not be had.
+async fn fetch_own<T: serde::de::DeserializeOwned + Unpin + Send>(
+    response: Result<twilight_http::Response<T>, twilight_http::Error>,
+) -> Result<T, CommandError> {
+    let failed = |why: &dyn std::fmt::Display| {
+        tracing::warn!("could not look up the bot's roles: {why}");
+        CommandError::OwnRolesUnknown
+    };
+    response
+        .map_err(|e| failed(&e))?
+        .model()
+        .await
+        .map_err(|e| failed(&e))
+}
+
+/// The id of `emoji`, failing unless it is one of `guild`'s custom emoji.
+fn check_emoji(
+    s: &State,
+    guild: Id<GuildMarker>,
+    emoji: &EmojiReactionType,
+) -> Result<Id<EmojiMarker>, CommandError> {
+    let id = emoji_id(emoji)?;
+    s.cache
+        .emoji(id)
+        .filter(|e| e.guild_id() == guild)
+        .map(|_| id)
+        .ok_or(CommandError::UnknownEmoji)
+}
+
+/// The id of `emoji` if it is a custom emoji, which may have since been deleted.
+/// For removing settings, which should still work once their emoji is gone.
+const fn emoji_id(emoji: &EmojiReactionType) -> Result<Id<EmojiMarker>, CommandError> {
+    match emoji {
+        EmojiReactionType::Custom { id, .. } => Ok(*id),
+        EmojiReactionType::Unicode { .. } => Err(CommandError::UnknownEmoji),
+    }
+}
+
+/// Parses `message` as a message id, failing unless it is a message in `channel`.
+async fn check_message(
+    s: &State,
+    channel: Id<ChannelMarker>,
+    message: &str,
+) -> Result<Id<MessageMarker>, CommandError> {
+    let message = message
+        .parse::<Id<MessageMarker>>()
+        .map_err(|_| CommandError::UnknownMessage)?;
+    s.http
+        .message(channel, message)
+        .await
+        .map_err(|_| CommandError::UnknownMessage)?;
+    Ok(message)
+}
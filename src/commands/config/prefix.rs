@@ -1,39 +1,121 @@
-use std::{future::Future, pin::Pin, sync::Arc};
+use super::update;
+use crate::{error::CommandError, parser::command};
 
-use twilight_model::{
-    channel::message::AllowedMentions, gateway::payload::incoming::MessageCreate,
-};
+/// The most prefixes a server can have at once.
+pub(super) const MAX_PREFIXES: usize = 10;
 
-use crate::{parser::CommandWithData, state::Handler, State};
+command! {
+    /// Manage the prefixes used for commands in this server. Mentioning the bot always works too.
+    #[category = Config]
+    #[permissions = MANAGE_GUILD]
+    #[guild_only = true]
+    #[default = set]
+    pub group prefix { list, set, add, remove, reset, ignorecase }
+}
+
+command! {
+    /// List this server's prefixes.
+    #[category = Config]
+    pub async fn list(s: State, ctx: Context) -> anyhow::Result<()> {
+        let config = s.config(Some(ctx.guild()?)).await;
 
-async fn prefix_impl(s: State, m: MessageCreate, c: CommandWithData) -> anyhow::Result<()> {
-    let mut maybe_pfx = None;
-    if let Some(args) = c.arguments {
-        if !args.is_empty() {
-            maybe_pfx = args[0].string();
+        let prefixes = config.prefixes(&s.bot.default_prefix);
+        let mut content = format!("Prefixes: `{}`", prefixes.join("`, `"));
+        if config.ignores_prefix_case() {
+            content += ", ignoring case";
         }
+        ctx.reply(&content).await
     }
+}
 
-    if let Some(pfx) = maybe_pfx {
-        let mut configs = s.server_configs.lock().await;
-        if let Some(config) = configs.get_mut(&m.guild_id.unwrap()) {
-            config.set_prefix(&pfx);
-
-            s.http
-                .create_message(m.channel_id)
-                .allowed_mentions(Some(&AllowedMentions::default()))
-                .content(&format!("Changed prefix to `{pfx}`."))
-                .reply(m.id)
-                .await?;
-        }
+command! {
+    /// Make a prefix the only prefix used for commands in this server.
+    #[category = Config]
+    pub async fn set(s: State, ctx: Context, new_prefix: String) -> anyhow::Result<()> {
+        check_prefix(&new_prefix)?;
+        let content = update(&s, ctx.guild()?, |config| {
+            config.set_prefix(&new_prefix);
+            format!("Changed prefix to `{new_prefix}`.")
+        })
+        .await?;
+        ctx.reply(&content).await
+    }
+}
+
+command! {
+    /// Add another prefix which can be used for commands in this server.
+    #[category = Config]
+    pub async fn add(s: State, ctx: Context, new_prefix: String) -> anyhow::Result<()> {
+        check_prefix(&new_prefix)?;
+        let default = &s.bot.default_prefix;
+        let content = update(&s, ctx.guild()?, |config| {
+            if config.prefixes(default).len() >= MAX_PREFIXES {
+                format!("A server can only have {MAX_PREFIXES} prefixes.")
+            } else if config.add_prefix(&new_prefix, default) {
+                format!("Added the prefix `{new_prefix}`.")
+            } else {
+                format!("`{new_prefix}` already is a prefix.")
+            }
+        })
+        .await?;
+        ctx.reply(&content).await
+    }
+}
+
+command! {
+    /// Stop a prefix from being used for commands in this server.
+    #[category = Config]
+    pub async fn remove(s: State, ctx: Context, old_prefix: String) -> anyhow::Result<()> {
+        let default = &s.bot.default_prefix;
+        let content = update(&s, ctx.guild()?, |config| {
+            if config.prefixes(default) == [old_prefix.as_str()] {
+                format!("`{old_prefix}` is the only prefix, add another one before removing it.")
+            } else if config.remove_prefix(&old_prefix, default) {
+                format!("Removed the prefix `{old_prefix}`.")
+            } else {
+                format!("`{old_prefix}` is not a prefix.")
+            }
+        })
+        .await?;
+        ctx.reply(&content).await
+    }
+}
+
+command! {
+    /// Go back to the default prefix.
+    #[category = Config]
+    pub async fn reset(s: State, ctx: Context) -> anyhow::Result<()> {
+        let content = update(&s, ctx.guild()?, |config| {
+            config.reset_prefixes();
+            format!("Reset the prefix to `{}`.", config.prefix(&s.bot.default_prefix))
+        })
+        .await?;
+        ctx.reply(&content).await
+    }
+}
+
+command! {
+    /// Choose whether prefixes and command names match regardless of case.
+    #[category = Config]
+    pub async fn ignorecase(s: State, ctx: Context, enabled: bool) -> anyhow::Result<()> {
+        let content = update(&s, ctx.guild()?, |config| {
+            config.ignore_prefix_case = Some(enabled);
+            if enabled {
+                String::from("Prefixes and commands now match regardless of case.")
+            } else {
+                String::from("Prefixes and commands are now case sensitive.")
+            }
+        })
+        .await?;
+        ctx.reply(&content).await
     }
-    Arc::clone(&s).write_configs_to_file().await?;
-    Ok(())
 }
-pub fn prefix(
-    s: State,
-    m: MessageCreate,
-    c: CommandWithData,
-) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + 'static>> {
-    Box::pin(prefix_impl(s, m, c))
+
+/// Fails unless `prefix` could be typed in front of a command, the same rule the default prefix
+/// is held to.
+pub(super) fn check_prefix(prefix: &str) -> Result<(), CommandError> {
+    if prefix.is_empty() || prefix.contains(char::is_whitespace) {
+        return Err(CommandError::InvalidPrefix);
+    }
+    Ok(())
 }
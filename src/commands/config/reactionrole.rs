@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use twilight_model::{
+    channel::message::EmojiReactionType,
+    id::{
+        marker::{ChannelMarker, RoleMarker},
+        Id,
+    },
+};
+
+use super::{check_channel, check_emoji, check_message, check_role, emoji_id, update};
+use crate::parser::command;
+
+command! {
+    /// Manage the roles members get by reacting to a message.
+    #[category = Config]
+    pub group reactionrole { add, remove }
+}
+
+command! {
+    /// Give a role to members who react to a message with an emoji.
+    #[category = Config]
+    pub async fn add(
+        s: State,
+        ctx: Context,
+        channel: Id<ChannelMarker>,
+        message_id: String,
+        emoji: EmojiReactionType,
+        role: Id<RoleMarker>,
+    ) -> anyhow::Result<()> {
+        let guild = ctx.guild()?;
+        check_channel(&s, guild, channel)?;
+        let message = check_message(&s, channel, &message_id).await?;
+        let emoji = check_emoji(&s, guild, &emoji)?;
+        check_role(&s, guild, role).await?;
+
+        let content = update(&s, guild, |config| {
+            config
+                .reaction_roles
+                .get_or_insert_with(HashMap::new)
+                .insert((message, emoji), role);
+            format!("Reacting to `{message}` with <:_:{emoji}> now gives <@&{role}>.")
+        })
+        .await?;
+        ctx.reply(&content).await
+    }
+}
+
+command! {
+    /// Stop giving a role for reacting to a message with an emoji.
+    #[category = Config]
+    pub async fn remove(
+        s: State,
+        ctx: Context,
+        message_id: String,
+        emoji: EmojiReactionType,
+    ) -> anyhow::Result<()> {
+        let guild = ctx.guild()?;
+        let emoji = emoji_id(&emoji)?;
+        let content = update(&s, guild, |config| {
+            let removed = message_id.parse().ok().and_then(|message| {
+                config.reaction_roles.as_mut()?.remove(&(message, emoji))
+            });
+            if config.reaction_roles.as_ref().is_some_and(HashMap::is_empty) {
+                config.reaction_roles = None;
+            }
+            match removed {
+                Some(role) => format!("Reacting to `{message_id}` no longer gives <@&{role}>."),
+                None => format!("Reacting to `{message_id}` with <:_:{emoji}> gives no role."),
+            }
+        })
+        .await?;
+        ctx.reply(&content).await
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+use twilight_model::id::{
+    marker::{ChannelMarker, RoleMarker},
+    Id,
+};
+
+use super::update;
+use crate::{
+    config::servers::{CommandRules, ServerConfig},
+    context::Context,
+    parser::{command, Category},
+    State,
+};
+
+command! {
+    /// Choose where commands, or whole categories of commands, can be used and by whom.
+    #[category = Config]
+    #[permissions = MANAGE_GUILD]
+    #[guild_only = true]
+    pub group commands { list, enable, disable, allowrole, denyrole, reset }
+}
+
+/// Finds the rules `name` refers to, a category or a top level command, creating them if needed.
+/// Category names take precedence over command names.
+/// Returns how to refer to them in a reply, or `None` if `name` is neither.
+fn rules_for<'a>(
+    s: &State,
+    config: &'a mut ServerConfig,
+    name: &str,
+) -> Option<(&'a mut CommandRules, String)> {
+    let name = name.to_lowercase();
+    if Category::ALL
+        .iter()
+        .any(|c| c.name().to_lowercase() == name)
+    {
+        let rules = config
+            .category_rules
+            .get_or_insert_with(HashMap::new)
+            .entry(name.clone())
+            .or_default();
+        return Some((rules, format!("{name} commands")));
+    }
+
+    let command = s.root_cmd.find_command(&name)?.name;
+    let rules = config
+        .command_rules
+        .get_or_insert_with(HashMap::new)
+        .entry(command.clone())
+        .or_default();
+    Some((rules, format!("`{command}`")))
+}
+
+/// Drops rules which no longer restrict anything.
+fn tidy(config: &mut ServerConfig) {
+    for rules in [&mut config.command_rules, &mut config.category_rules]
+        .into_iter()
+        .flatten()
+    {
+        rules.retain(|_, r| !r.is_empty());
+    }
+}
+
+/// Changes the rules for `target` and replies with what changed.
+async fn change_rules(
+    s: &State,
+    ctx: &Context,
+    target: &str,
+    change: impl FnOnce(&mut CommandRules, &str) -> String,
+) -> anyhow::Result<()> {
+    let content = update(s, ctx.guild()?, |config| {
+        let content = match rules_for(s, config, target) {
+            Some((rules, what)) => change(rules, &what),
+            None => format!("There is no command or category called `{target}`."),
+        };
+        tidy(config);
+        content
+    })
+    .await?;
+    ctx.reply(&content).await
+}
+
+/// One line of `commands list`, e.g. `command play: only in #music`.
+pub(super) fn describe(kind: &str, name: &str, rules: &CommandRules) -> String {
+    let mut line = format!("{kind} {name}:");
+    if rules.disabled {
+        line += " disabled";
+    }
+    let channels = |ids: &[Id<ChannelMarker>]| ids.iter().map(|c| format!("<#{c}>")).collect();
+    let mentions = |ids: &[Id<RoleMarker>]| ids.iter().map(|r| format!("<@&{r}>")).collect();
+    let parts: [(&str, Vec<String>); 4] = [
+        ("only in", channels(&rules.allowed_channels)),
+        ("not in", channels(&rules.denied_channels)),
+        ("only for", mentions(&rules.allowed_roles)),
+        ("not for", mentions(&rules.denied_roles)),
+    ];
+    for (label, items) in parts {
+        if !items.is_empty() {
+            line += &format!(" {label} {}", items.join(" "));
+        }
+    }
+    line
+}
+
+fn add<T: PartialEq>(list: &mut Vec<T>, item: T) {
+    if !list.contains(&item) {
+        list.push(item);
+    }
+}
+
+command! {
+    /// Show the rules this server has for commands.
+    #[category = Config]
+    pub async fn list(s: State, ctx: Context) -> anyhow::Result<()> {
+        let config = s.config(Some(ctx.guild()?)).await;
+
+        let mut lines = config
+            .category_rules
+            .iter()
+            .flatten()
+            .map(|(name, rules)| describe("category", name, rules))
+            .chain(
+                config
+                    .command_rules
+                    .iter()
+                    .flatten()
+                    .map(|(name, rules)| describe("command", name, rules)),
+            )
+            .collect::<Vec<_>>();
+
+        let content = if lines.is_empty() {
+            String::from("Every command can be used everywhere.")
+        } else {
+            lines.sort_unstable();
+            lines.join("\n")
+        };
+        ctx.reply(&content).await
+    }
+}
+
+command! {
+    /// Turn a command or category back on, or with a channel, only allow it there.
+    #[category = Config]
+    pub async fn enable(
+        s: State,
+        ctx: Context,
+        command_or_category: String,
+        channel: Option<Id<ChannelMarker>>,
+    ) -> anyhow::Result<()> {
+        change_rules(&s, &ctx, &command_or_category, |rules, what| match channel {
+            Some(channel) => {
+                rules.denied_channels.retain(|c| *c != channel);
+                add(&mut rules.allowed_channels, channel);
+                format!(
+                    "You can now use {what} in <#{channel}>, \
+                    along with any other channels it was enabled in."
+                )
+            }
+            None => {
+                rules.disabled = false;
+                rules.denied_channels.clear();
+                format!("Enabled {what}.")
+            }
+        })
+        .await
+    }
+}
+
+command! {
+    /// Turn a command or category off, or with a channel, only there.
+    #[category = Config]
+    pub async fn disable(
+        s: State,
+        ctx: Context,
+        command_or_category: String,
+        channel: Option<Id<ChannelMarker>>,
+    ) -> anyhow::Result<()> {
+        change_rules(&s, &ctx, &command_or_category, |rules, what| match channel {
+            Some(channel) => {
+                rules.allowed_channels.retain(|c| *c != channel);
+                add(&mut rules.denied_channels, channel);
+                format!("You can no longer use {what} in <#{channel}>.")
+            }
+            None => {
+                rules.disabled = true;
+                format!("Disabled {what}.")
+            }
+        })
+        .await
+    }
+}
+
+command! {
+    /// Only let members with a role use a command or category.
+    #[category = Config]
+    pub async fn allowrole(
+        s: State,
+        ctx: Context,
+        command_or_category: String,
+        role: Id<RoleMarker>,
+    ) -> anyhow::Result<()> {
+        change_rules(&s, &ctx, &command_or_category, |rules, what| {
+            rules.denied_roles.retain(|r| *r != role);
+            add(&mut rules.allowed_roles, role);
+            format!("Members with <@&{role}> can now use {what}.")
+        })
+        .await
+    }
+}
+
+command! {
+    /// Stop members with a role from using a command or category.
+    #[category = Config]
+    pub async fn denyrole(
+        s: State,
+        ctx: Context,
+        command_or_category: String,
+        role: Id<RoleMarker>,
+    ) -> anyhow::Result<()> {
+        change_rules(&s, &ctx, &command_or_category, |rules, what| {
+            rules.allowed_roles.retain(|r| *r != role);
+            add(&mut rules.denied_roles, role);
+            format!("Members with <@&{role}> can no longer use {what}.")
+        })
+        .await
+    }
+}
+
+command! {
+    /// Remove every rule for a command or category.
+    #[category = Config]
+    pub async fn reset(s: State, ctx: Context, command_or_category: String) -> anyhow::Result<()> {
+        change_rules(&s, &ctx, &command_or_category, |rules, what| {
+            *rules = CommandRules::default();
+            format!("{what} can now be used everywhere, by everyone.")
+        })
+        .await
+    }
+}
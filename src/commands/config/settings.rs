@@ -0,0 +1,275 @@
+use twilight_model::{
+    channel::message::embed::EmbedField,
+    id::{
+        marker::{ChannelMarker, RoleMarker},
+        Id,
+    },
+};
+
+use super::{
+    autoreact::autoreact,
+    autoresponse::autoresponse,
+    blacklist::blacklist,
+    check_channel, check_role,
+    reactionrole::reactionrole,
+    rules,
+    significant::significant,
+    transfer::{export, import},
+    update,
+};
+use crate::{context::embed, parser::command};
+
+/// Discord refuses embed fields longer than this.
+const MAX_FIELD_LENGTH: usize = 1024;
+
+command! {
+    /// Show and change this server's settings.
+    #[category = Config]
+    #[permissions = MANAGE_GUILD]
+    #[guild_only = true]
+    pub group config {
+        show,
+        export,
+        import,
+        log,
+        spam,
+        muterole,
+        reactioncount,
+        blacklist,
+        autoresponse,
+        autoreact,
+        reactionrole,
+        significant,
+    }
+}
+
+/// An embed field listing `items` one per line, cut short if there are too many to fit.
+fn field(name: &str, mut items: Vec<String>) -> EmbedField {
+    items.sort_unstable();
+    let mut value = if items.is_empty() {
+        String::from("None")
+    } else {
+        items.join("\n")
+    };
+    if value.len() > MAX_FIELD_LENGTH {
+        let end = (0..MAX_FIELD_LENGTH - 4)
+            .rev()
+            .find(|i| value.is_char_boundary(*i))
+            .unwrap_or(0);
+        value.truncate(end);
+        value += "\n...";
+    }
+    EmbedField {
+        inline: false,
+        name: String::from(name),
+        value,
+    }
+}
+
+command! {
+    /// Show every setting of this server.
+    #[category = Config]
+    pub async fn show(s: State, ctx: Context) -> anyhow::Result<()> {
+        let config = s.config(Some(ctx.guild()?)).await;
+
+        let pfx = config.prefix(&s.bot.default_prefix);
+        let mut description = format!(
+            "Prefixes: `{}`",
+            config.prefixes(&s.bot.default_prefix).join("`, `")
+        );
+        if config.ignores_prefix_case() {
+            description += ", ignoring case";
+        }
+        description += "\nCommand suggestions: ";
+        description += if config.suggests_commands() {
+            "on"
+        } else {
+            "off"
+        };
+        let mut settings = embed("Settings", &description);
+        let significant = config
+            .significant_reaction_count
+            .map(|count| format!("At {count} reactions"))
+            .into_iter()
+            .chain(
+                config
+                    .channels
+                    .significant_reactions
+                    .iter()
+                    .flatten()
+                    .map(|(emoji, channel)| format!("<:_:{emoji}> to <#{channel}>")),
+            )
+            .collect();
+        settings.fields = vec![
+            field(
+                "Log channel",
+                config.channels.log.map(|c| format!("<#{c}>")).into_iter().collect(),
+            ),
+            field(
+                "Spam channel",
+                config.channels.spam.map(|c| format!("<#{c}>")).into_iter().collect(),
+            ),
+            field(
+                "Mute role",
+                config.mute_role.map(|r| format!("<@&{r}>")).into_iter().collect(),
+            ),
+            field("Significant reactions", significant),
+            field(
+                "Blacklisted words",
+                config
+                    .blacklisted_words
+                    .iter()
+                    .flatten()
+                    .map(|w| format!("`{w}`"))
+                    .collect(),
+            ),
+            field(
+                "Auto responses",
+                config
+                    .auto_responses
+                    .iter()
+                    .flatten()
+                    .map(|(trigger, response)| format!("`{trigger}`: {response}"))
+                    .collect(),
+            ),
+            field(
+                "Auto reacts",
+                config
+                    .auto_reacts
+                    .iter()
+                    .flatten()
+                    .map(|(trigger, emoji)| format!("`{trigger}`: <:_:{emoji}>"))
+                    .collect(),
+            ),
+            field(
+                "Reaction roles",
+                config
+                    .reaction_roles
+                    .iter()
+                    .flatten()
+                    .map(|((message, emoji), role)| {
+                        format!("`{message}` <:_:{emoji}>: <@&{role}>")
+                    })
+                    .collect(),
+            ),
+            field(
+                "Aliases",
+                config
+                    .aliases
+                    .iter()
+                    .flatten()
+                    .map(|(name, expansion)| format!("`{pfx}{name}`: `{pfx}{expansion}`"))
+                    .collect(),
+            ),
+            field(
+                "Command rules",
+                config
+                    .category_rules
+                    .iter()
+                    .flatten()
+                    .map(|(name, r)| rules::describe("category", name, r))
+                    .chain(
+                        config
+                            .command_rules
+                            .iter()
+                            .flatten()
+                            .map(|(name, r)| rules::describe("command", name, r)),
+                    )
+                    .collect(),
+            ),
+        ];
+        ctx.reply_embed(settings).await
+    }
+}
+
+command! {
+    /// Set the channel moderation actions are logged in, or stop logging when left out.
+    #[category = Config]
+    pub async fn log(
+        s: State,
+        ctx: Context,
+        channel: Option<Id<ChannelMarker>>,
+    ) -> anyhow::Result<()> {
+        let guild = ctx.guild()?;
+        if let Some(channel) = channel {
+            check_channel(&s, guild, channel)?;
+        }
+        let content = update(&s, guild, |config| {
+            config.channels.log = channel;
+            channel.map_or_else(
+                || String::from("Stopped logging."),
+                |c| format!("Logging to <#{c}>."),
+            )
+        })
+        .await?;
+        ctx.reply(&content).await
+    }
+}
+
+command! {
+    /// Set the channel for bot spam, or unset it when left out.
+    #[category = Config]
+    pub async fn spam(
+        s: State,
+        ctx: Context,
+        channel: Option<Id<ChannelMarker>>,
+    ) -> anyhow::Result<()> {
+        let guild = ctx.guild()?;
+        if let Some(channel) = channel {
+            check_channel(&s, guild, channel)?;
+        }
+        let content = update(&s, guild, |config| {
+            config.channels.spam = channel;
+            channel.map_or_else(
+                || String::from("Unset the spam channel."),
+                |c| format!("The spam channel is now <#{c}>."),
+            )
+        })
+        .await?;
+        ctx.reply(&content).await
+    }
+}
+
+command! {
+    /// Set the role given to muted members, or unset it when left out.
+    #[category = Config]
+    pub async fn muterole(
+        s: State,
+        ctx: Context,
+        role: Option<Id<RoleMarker>>,
+    ) -> anyhow::Result<()> {
+        let guild = ctx.guild()?;
+        if let Some(role) = role {
+            check_role(&s, guild, role).await?;
+        }
+        let content = update(&s, guild, |config| {
+            config.mute_role = role;
+            role.map_or_else(
+                || String::from("Unset the mute role."),
+                |r| format!("The mute role is now <@&{r}>."),
+            )
+        })
+        .await?;
+        ctx.reply(&content).await
+    }
+}
+
+command! {
+    /// Set how many reactions make a message significant, or turn significant reactions off when
+    /// left out.
+    #[category = Config]
+    pub async fn reactioncount(s: State, ctx: Context, count: Option<u32>) -> anyhow::Result<()> {
+        if count == Some(0) {
+            return ctx.reply("A message needs at least one reaction to be significant.").await;
+        }
+        let content = update(&s, ctx.guild()?, |config| {
+            config.significant_reaction_count = count;
+            count.map_or_else(
+                || String::from("Turned significant reactions off."),
+                |n| format!("Messages are now significant at {n} reactions."),
+            )
+        })
+        .await?;
+        ctx.reply(&content).await
+    }
+}
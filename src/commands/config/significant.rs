@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use twilight_model::{
+    channel::message::EmojiReactionType,
+    id::{marker::ChannelMarker, Id},
+};
+
+use super::{check_channel, check_emoji, emoji_id, update};
+use crate::parser::command;
+
+command! {
+    /// Manage where messages with many of the same reaction are reposted.
+    #[category = Config]
+    pub group significant { add, remove }
+}
+
+command! {
+    /// Repost messages with enough of an emoji's reactions in a channel.
+    #[category = Config]
+    pub async fn add(
+        s: State,
+        ctx: Context,
+        emoji: EmojiReactionType,
+        channel: Id<ChannelMarker>,
+    ) -> anyhow::Result<()> {
+        let guild = ctx.guild()?;
+        let emoji = check_emoji(&s, guild, &emoji)?;
+        check_channel(&s, guild, channel)?;
+        let content = update(&s, guild, |config| {
+            config
+                .channels
+                .significant_reactions
+                .get_or_insert_with(HashMap::new)
+                .insert(emoji, channel);
+            let mut content = format!("Messages with enough <:_:{emoji}> go to <#{channel}>.");
+            if config.significant_reaction_count.is_none() {
+                content += " Set how many are enough with `config reactioncount`.";
+            }
+            content
+        })
+        .await?;
+        ctx.reply(&content).await
+    }
+}
+
+command! {
+    /// Stop reposting messages with an emoji's reactions.
+    #[category = Config]
+    pub async fn remove(s: State, ctx: Context, emoji: EmojiReactionType) -> anyhow::Result<()> {
+        let guild = ctx.guild()?;
+        let emoji = emoji_id(&emoji)?;
+        let content = update(&s, guild, |config| {
+            let reactions = &mut config.channels.significant_reactions;
+            let removed = reactions.as_mut().and_then(|r| r.remove(&emoji));
+            if reactions.as_ref().is_some_and(HashMap::is_empty) {
+                *reactions = None;
+            }
+            match removed {
+                Some(_) => format!("Stopped reposting messages for <:_:{emoji}>."),
+                None => format!("Messages are not reposted for <:_:{emoji}>."),
+            }
+        })
+        .await?;
+        ctx.reply(&content).await
+    }
+}
@@ -0,0 +1,21 @@
+use super::update;
+use crate::parser::command;
+
+command! {
+    /// Turn "did you mean" replies to unknown commands on or off.
+    #[category = Config]
+    #[permissions = MANAGE_GUILD]
+    #[guild_only = true]
+    pub async fn suggestions(s: State, ctx: Context, enabled: bool) -> anyhow::Result<()> {
+        let content = update(&s, ctx.guild()?, |config| {
+            config.suggest_commands = Some(enabled);
+            if enabled {
+                String::from("Unknown commands will now be answered with suggestions.")
+            } else {
+                String::from("Unknown commands will now be ignored.")
+            }
+        })
+        .await?;
+        ctx.reply(&content).await
+    }
+}
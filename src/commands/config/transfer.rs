@@ -0,0 +1,369 @@
+use std::time::Duration;
+
+use twilight_model::{
+    channel::message::EmojiReactionType,
+    gateway::payload::incoming::MessageCreate,
+    http::attachment::Attachment,
+    id::{marker::GuildMarker, Id},
+};
+
+use super::{
+    check_channel, check_emoji, check_role_below, highest_role,
+    prefix::{check_prefix, MAX_PREFIXES},
+    update,
+};
+use crate::{
+    config::{
+        portable::{Format, PortableConfig},
+        servers::ServerConfig,
+    },
+    error::CommandError,
+    parser::{command, Category, Command, TextCommand},
+    State,
+};
+
+/// Imported files larger than this are refused, a config is a few KiB at most.
+const MAX_IMPORT_SIZE: usize = 64 * 1024;
+/// How long the author has to confirm an import.
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+/// Leaves room in the 2000 character message limit for the text around the diff.
+const MAX_DIFF_LENGTH: usize = 1500;
+
+command! {
+    /// Upload this server's settings as a file, as `toml` or `json`.
+    #[category = Config]
+    pub async fn export(s: State, ctx: Context, format: Option<String>) -> anyhow::Result<()> {
+        let format = match format.as_deref() {
+            None => Format::Toml,
+            Some(name) => match Format::from_filename(&format!(".{name}")) {
+                Some(format) => format,
+                None => return ctx.reply("Settings can be exported as `toml` or `json`.").await,
+            },
+        };
+        let config = PortableConfig::from(&s.config(Some(ctx.guild()?)).await);
+        let file = Attachment::from_bytes(
+            format!("config.{}", format.extension()),
+            format.encode(&config)?.into_bytes(),
+            0,
+        );
+        ctx.reply_file("This server's settings:", file).await
+    }
+}
+
+command! {
+    /// Replace this server's settings with those of an attached `toml` or `json` file.
+    #[category = Config]
+    #[takes_file = true]
+    pub async fn import(s: State, ctx: Context) -> anyhow::Result<()> {
+        let guild = ctx.guild()?;
+        // Only files uploaded to Discord are fetched, never a URL someone typed.
+        let Some(attachment) = ctx.attachments.first() else {
+            return ctx.reply("Attach the settings file to import.").await;
+        };
+        if attachment.size > MAX_IMPORT_SIZE as u64 {
+            return Err(CommandError::InvalidImport(String::from("it is too large")).into());
+        }
+        ctx.defer().await?;
+
+        let format = Format::from_filename(&attachment.filename).ok_or_else(|| {
+            CommandError::InvalidImport(String::from("its name should end in `.toml` or `.json`"))
+        })?;
+        let imported = format
+            .decode(&download(&s, &attachment.url).await?)
+            .map_err(CommandError::InvalidImport)?;
+
+        let mut problems = check_settings(&s.root_cmd, &s.bot.default_prefix, &imported);
+        problems.extend(validate(&s, guild, &imported).await?);
+        if !problems.is_empty() {
+            let problems = problems.join("\n");
+            let why = format!("some of its settings can't be used:\n{problems}");
+            return Err(CommandError::InvalidImport(why).into());
+        }
+
+        let current = PortableConfig::from(&s.config(Some(guild)).await);
+        let diff = current.diff(&imported)?;
+        if diff.is_empty() {
+            return ctx.reply("This server's settings already match that file.").await;
+        }
+        ctx.reply(&format!(
+            "Importing would change:\n```diff\n{}\n```Reply `yes` within {} seconds to apply it.",
+            truncate(&diff),
+            CONFIRM_TIMEOUT.as_secs()
+        ))
+        .await?;
+
+        let author = ctx.author.id;
+        let answer = s
+            .standby
+            .wait_for_message(ctx.channel_id, move |m: &MessageCreate| m.author.id == author);
+        let confirmed = matches!(
+            tokio::time::timeout(CONFIRM_TIMEOUT, answer).await,
+            Ok(Ok(m)) if m.content.trim().eq_ignore_ascii_case("yes")
+        );
+        if !confirmed {
+            return ctx.followup("Import cancelled, nothing was changed.").await;
+        }
+
+        // The diff was made before waiting for an answer, so only apply it if it still holds.
+        let content = update(&s, guild, |config| {
+            if PortableConfig::from(&*config) != current {
+                return String::from(
+                    "This server's settings changed while waiting, nothing was imported. Try again to see the new changes.",
+                );
+            }
+            imported.apply(config);
+            String::from("Imported the settings.")
+        })
+        .await?;
+        ctx.followup(&content).await
+    }
+}
+
+/// Fetches the attachment at `url`, refusing anything too large to be a config.
+async fn download(s: &State, url: &str) -> Result<String, CommandError> {
+    let failed = |why: &str| CommandError::InvalidImport(format!("it {why}"));
+    let response = s
+        .client
+        .get(url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|_| failed("could not be downloaded"))?;
+    if response
+        .content_length()
+        .is_some_and(|len| len > MAX_IMPORT_SIZE as u64)
+    {
+        return Err(failed("is too large"));
+    }
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|_| failed("could not be downloaded"))?;
+    if bytes.len() > MAX_IMPORT_SIZE {
+        return Err(failed("is too large"));
+    }
+    String::from_utf8(bytes.to_vec()).map_err(|_| failed("is not text"))
+}
+
+/// Everything in `config` which could not work in any server, one problem per line: prefixes
+/// which can't be typed or are too many, aliases which do not run a command and rules for
+/// commands or categories which do not exist.
+fn check_settings(root: &Command, default_prefix: &str, config: &PortableConfig) -> Vec<String> {
+    let mut problems = vec![];
+
+    for pfx in &config.prefixes {
+        if let Err(e) = check_prefix(pfx) {
+            problems.push(format!("`prefixes`: `{pfx}`: {e}"));
+        }
+    }
+    let mut applied = ServerConfig::new();
+    config.clone().apply(&mut applied);
+    if applied.prefixes(default_prefix).len() > MAX_PREFIXES {
+        problems.push(format!(
+            "`prefixes`: a server can only have {MAX_PREFIXES} prefixes."
+        ));
+    }
+
+    for (name, expansion) in &config.aliases {
+        if root.find_command(name).is_some() {
+            problems.push(format!("`aliases`: `{name}` is already a command."));
+        }
+        let target = TextCommand::new(expansion).next().unwrap_or_default();
+        if root.find_command(&target).is_none() {
+            problems.push(format!(
+                "`aliases`: `{name}` runs `{target}`, which is not a command."
+            ));
+        }
+    }
+
+    // Rules are kept under a command's name, never one of its aliases.
+    for name in config.command_rules.keys() {
+        if root.find_command(name).is_none_or(|c| c.name != *name) {
+            problems.push(format!(
+                "`command_rules`: there is no command called `{name}`."
+            ));
+        }
+    }
+    for name in config.category_rules.keys() {
+        if !Category::ALL
+            .iter()
+            .any(|c| c.name().to_lowercase() == *name)
+        {
+            problems.push(format!(
+                "`category_rules`: there is no category called `{name}`."
+            ));
+        }
+    }
+
+    problems
+}
+
+/// Everything in `config` which does not belong to `guild`, one problem per line.
+async fn validate(
+    s: &State,
+    guild: Id<GuildMarker>,
+    config: &PortableConfig,
+) -> Result<Vec<String>, CommandError> {
+    // Only looked up when needed, so a failed lookup doesn't block files without roles to give.
+    let highest = if config.mute_role.is_some() || !config.reaction_roles.is_empty() {
+        highest_role(s, guild).await?
+    } else {
+        0
+    };
+    let mut problems = vec![];
+    let mut problem = |setting: &str, error: CommandError| {
+        problems.push(format!("`{setting}`: {error}"));
+    };
+    let emoji = |id| EmojiReactionType::Custom {
+        animated: false,
+        id,
+        name: None,
+    };
+
+    for (setting, channel) in [
+        ("log_channel", config.log_channel),
+        ("spam_channel", config.spam_channel),
+    ] {
+        if let Some(Err(e)) = channel.map(|c| check_channel(s, guild, c)) {
+            problem(setting, e);
+        }
+    }
+    if let Some(Err(e)) = config
+        .mute_role
+        .map(|r| check_role_below(s, guild, r, highest))
+    {
+        problem("mute_role", e);
+    }
+    for id in config.auto_reacts.values() {
+        if let Err(e) = check_emoji(s, guild, &emoji(*id)) {
+            problem("auto_reacts", e);
+        }
+    }
+    for reaction in &config.significant_reactions {
+        if let Err(e) = check_emoji(s, guild, &emoji(reaction.emoji))
+            .map(|_| ())
+            .and_then(|()| check_channel(s, guild, reaction.channel))
+        {
+            problem("significant_reactions", e);
+        }
+    }
+    for reaction in &config.reaction_roles {
+        if let Err(e) = check_emoji(s, guild, &emoji(reaction.emoji))
+            .map(|_| ())
+            .and_then(|()| check_role_below(s, guild, reaction.role, highest))
+        {
+            problem("reaction_roles", e);
+        }
+    }
+    for (name, rules) in config.command_rules.iter().chain(&config.category_rules) {
+        for channel in rules.allowed_channels.iter().chain(&rules.denied_channels) {
+            if let Err(e) = check_channel(s, guild, *channel) {
+                problem(name, e);
+            }
+        }
+        for role in rules.allowed_roles.iter().chain(&rules.denied_roles) {
+            if s.cache.role(*role).is_none_or(|r| r.guild_id() != guild) {
+                problem(name, CommandError::UnknownRole);
+            }
+        }
+    }
+
+    Ok(problems)
+}
+
+/// Joins the lines of `diff`, leaving out those which do not fit in a message.
+fn truncate(diff: &[String]) -> String {
+    let mut content = String::new();
+    for (i, line) in diff.iter().enumerate() {
+        if content.len() + line.len() > MAX_DIFF_LENGTH {
+            content += &format!("... and {} more lines", diff.len() - i);
+            break;
+        }
+        content += line;
+        content.push('\n');
+    }
+    content.trim_end().to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{check_settings, MAX_PREFIXES};
+    use crate::{
+        config::{portable::PortableConfig, servers::CommandRules},
+        parser::Command,
+    };
+
+    fn root() -> Command {
+        let play = Command::new(String::from("play"), None, &[], &[]).aliases(&["p"]);
+        Command::new(String::new(), None, &[play], &[])
+    }
+
+    fn problems(config: &PortableConfig) -> Vec<String> {
+        check_settings(&root(), "~", config)
+    }
+
+    #[test]
+    fn accepts_settings_which_work() {
+        let config = PortableConfig {
+            prefixes: vec![String::from("!")],
+            aliases: BTreeMap::from([(String::from("lofi"), String::from("play lofi"))]),
+            command_rules: BTreeMap::from([(String::from("play"), CommandRules::default())]),
+            category_rules: BTreeMap::from([(String::from("music"), CommandRules::default())]),
+            ..PortableConfig::default()
+        };
+        assert!(problems(&config).is_empty());
+    }
+
+    #[test]
+    fn rejects_prefixes_which_cannot_be_typed() {
+        let config = PortableConfig {
+            prefixes: vec![String::new(), String::from("a b")],
+            ..PortableConfig::default()
+        };
+        assert_eq!(problems(&config).len(), 2);
+    }
+
+    #[test]
+    fn rejects_too_many_prefixes() {
+        // The default prefix is kept, making one too many.
+        let config = PortableConfig {
+            prefixes: (0..MAX_PREFIXES).map(|i| format!("!{i}")).collect(),
+            ..PortableConfig::default()
+        };
+        assert_eq!(problems(&config).len(), 1);
+
+        let config = PortableConfig {
+            keep_default_prefix: Some(false),
+            ..config
+        };
+        assert!(problems(&config).is_empty());
+    }
+
+    #[test]
+    fn rejects_aliases_which_do_not_run_a_command() {
+        let config = PortableConfig {
+            aliases: BTreeMap::from([
+                (String::from("lofi"), String::from("plya lofi")),
+                (String::from("empty"), String::new()),
+                (String::from("p"), String::from("play")),
+            ]),
+            ..PortableConfig::default()
+        };
+        assert_eq!(problems(&config).len(), 3);
+    }
+
+    #[test]
+    fn rejects_rules_for_unknown_commands_and_categories() {
+        let config = PortableConfig {
+            command_rules: BTreeMap::from([
+                (String::from("plya"), CommandRules::default()),
+                (String::from("p"), CommandRules::default()),
+            ]),
+            category_rules: BTreeMap::from([(String::from("Music"), CommandRules::default())]),
+            ..PortableConfig::default()
+        };
+        assert_eq!(problems(&config).len(), 3);
+    }
+}
@@ -1,52 +1,33 @@
-use std::{
-    future::Future,
-    pin::Pin,
-    random::{DefaultRandomSource, Random},
-};
+use std::random::{DefaultRandomSource, Random};
 
-use twilight_model::{
-    channel::message::AllowedMentions, gateway::payload::incoming::MessageCreate,
-};
+use crate::parser::{command, Command};
 
-use crate::{parser::CommandWithData, State};
+/// The top level commands defined here, in the order `help` lists them.
+pub fn top_level() -> Vec<Command> {
+    vec![jump()]
+}
 
-async fn jump_impl(s: State, m: MessageCreate, c: CommandWithData) -> anyhow::Result<()> {
-    let mut amount = 1;
-    if let Some(args) = c.arguments {
-        if !args.is_empty() {
-            amount = args[0].clone().uint().unwrap_or(1);
-        }
-    };
+command! {
+    /// Attempt the jump. Up to 10 times, if you dare.
+    #[category = Fun]
+    #[cooldown(User, 10)]
+    pub async fn jump(_s: State, ctx: Context, amount: Option<u32>) -> anyhow::Result<()> {
+        let amount = amount.unwrap_or(1);
 
-    if amount <= 10 {
-        for _ in 0..amount {
-            let res = bool::random(&mut DefaultRandomSource);
-            let content = if res {
-                "YOU MADE THE JUMP!!! YOURE SO AWESOME. HERE'S THE BEEF."
-            } else {
-                "CONGRATULATIONS, YOU'VE DROPPED YOURSELF INTO NOOB JAIL, DUMBASS."
-            };
-            s.http
-                .create_message(m.channel_id)
-                .allowed_mentions(Some(&AllowedMentions::default()))
-                .content(content)
-                .reply(m.id)
+        if amount <= 10 {
+            for _ in 0..amount {
+                let res = bool::random(&mut DefaultRandomSource);
+                let content = if res {
+                    "YOU MADE THE JUMP!!! YOURE SO AWESOME. HERE'S THE BEEF."
+                } else {
+                    "CONGRATULATIONS, YOU'VE DROPPED YOURSELF INTO NOOB JAIL, DUMBASS."
+                };
+                ctx.reply(content).await?;
+            }
+        } else {
+            ctx.reply("YOU TOOK THE FIZZY LIFTING DRINKS. YOU LOSE. GOOD DAY TO YOU.")
                 .await?;
         }
-    } else {
-        s.http
-            .create_message(m.channel_id)
-            .allowed_mentions(Some(&AllowedMentions::default()))
-            .content("YOU TOOK THE FIZZY LIFTING DRINKS. YOU LOSE. GOOD DAY TO YOU.")
-            .reply(m.id)
-            .await?;
+        Ok(())
     }
-    Ok(())
-}
-pub fn jump(
-    s: State,
-    m: MessageCreate,
-    c: CommandWithData,
-) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + 'static>> {
-    return (move |sc, mc, cc| Box::pin(jump_impl(sc, mc, cc)))(s, m, c);
 }
@@ -0,0 +1,132 @@
+use crate::parser::{command, permission_names, Bucket, Category, Command, Rest};
+
+/// The top level commands defined here, in the order `help` lists them.
+pub fn top_level() -> Vec<Command> {
+    vec![help()]
+}
+
+/// One page of the overview per category, skipping categories with no commands.
+fn overview_pages(root: &Command, pfx: &str) -> Vec<String> {
+    let commands = root.subcommands.iter().flatten().collect::<Vec<_>>();
+    Category::ALL
+        .into_iter()
+        .filter_map(|category| {
+            let in_category = commands
+                .iter()
+                .filter(|c| c.category == category)
+                .collect::<Vec<_>>();
+            if in_category.is_empty() {
+                return None;
+            }
+
+            let width = in_category
+                .iter()
+                .map(|c| c.usage(pfx).len())
+                .max()
+                .unwrap_or(0);
+            let mut page = format!("**{}**\n```\n", category.name());
+            for c in in_category {
+                page += &format!("{:width$}  {}\n", c.usage(pfx), c.description);
+            }
+            page += "```";
+            Some(page)
+        })
+        .collect()
+}
+
+/// Finds a command by its path, e.g. `["config", "log"]`,
+/// returning it along with the names of its parents, e.g. `"config "`.
+fn walk<'a>(root: &'a Command, path: &[&str]) -> Option<(&'a Command, String)> {
+    let mut cmd = root;
+    let mut parents = String::new();
+    for (i, name) in path.iter().enumerate() {
+        cmd = cmd
+            .subcommands
+            .iter()
+            .flatten()
+            .find(|c| c.is_called(name))?;
+        if i + 1 < path.len() {
+            parents += &format!("{} ", cmd.name);
+        }
+    }
+    Some((cmd, parents))
+}
+
+/// The detailed help for a single command, including its usage and subcommands.
+pub fn describe(cmd: &Command, parents: &str, pfx: &str) -> String {
+    let full_name = format!("{pfx}{parents}{}", cmd.name);
+
+    let mut content = format!("**{full_name}**");
+    if !cmd.description.is_empty() {
+        content += &format!(" - {}", cmd.description);
+    }
+    content += &format!("\nCategory: {}", cmd.category.name());
+    if !cmd.aliases.is_empty() {
+        content += &format!("\nAliases: `{}`", cmd.aliases.join("`, `"));
+    }
+    if !cmd.permissions.is_empty() {
+        content += &format!("\nRequires: {}", permission_names(cmd.permissions));
+    }
+    if let Some(cooldown) = cmd.cooldown {
+        let per = match cooldown.bucket {
+            Bucket::User => "user",
+            Bucket::Channel => "channel",
+            Bucket::Guild => "server",
+        };
+        content += &format!("\nCooldown: {}s per {per}", cooldown.duration.as_secs());
+    }
+    if cmd.guild_only {
+        content += "\nOnly works in servers";
+    }
+    if cmd.function.is_some() {
+        content += &format!("\nUsage: `{}`", cmd.usage(&format!("{pfx}{parents}")));
+    }
+    if let Some(default) = &cmd.default_subcommand {
+        content += &format!("\n`{full_name} ...` is short for `{full_name} {default} ...`");
+    }
+
+    let subcommands = cmd.subcommands.iter().flatten().collect::<Vec<_>>();
+    if !subcommands.is_empty() {
+        content += "\nSubcommands:";
+        for sc in subcommands {
+            content += &format!("\n- `{full_name} {}` {}", sc.name, sc.description);
+        }
+    }
+    content
+}
+
+command! {
+    /// List every command, or show how to use one.
+    #[category = Utility]
+    pub async fn help(s: State, ctx: Context, command_or_page: Option<Rest>) -> anyhow::Result<()> {
+        let pfx = s.config(ctx.guild_id).await.prefix(&s.bot.default_prefix);
+
+        let query = command_or_page.map(|q| q.0).unwrap_or_default();
+
+        let pages = overview_pages(&s.root_cmd, &pfx);
+        let content = if let Some(page) = query
+            .parse::<usize>()
+            .ok()
+            .or_else(|| query.is_empty().then_some(1))
+        {
+            // Pages are numbered from 1, so `help 0` shows the first page too.
+            let page = page.max(1);
+            match pages.get(page - 1) {
+                Some(listing) => format!(
+                    "{listing}\nPage {page}/{}. Use `{pfx}help <page>` to see another page, or `{pfx}help <command>` for details.",
+                    pages.len()
+                ),
+                None => format!("There are only {} pages of help.", pages.len()),
+            }
+        } else {
+            let path = query.split_whitespace().collect::<Vec<_>>();
+            match walk(&s.root_cmd, &path) {
+                Some((cmd, parents)) => describe(cmd, &parents, &pfx),
+                None => format!("There is no command called `{query}`."),
+            }
+        };
+
+        ctx.reply(&content).await?;
+        Ok(())
+    }
+}
@@ -4,18 +4,21 @@ mod play;
 mod queue;
 mod skip;
 
-pub use join::join;
-pub use join::leave;
+use crate::parser::Command;
 
-pub use pause::pause;
-pub use pause::unpause;
-
-pub use play::play;
-
-pub use skip::next;
-pub use skip::prev;
-
-pub use queue::insert;
-pub use queue::playnext;
-pub use queue::queue;
-pub use queue::remove;
+/// The top level commands defined here, in the order `help` lists them.
+pub fn top_level() -> Vec<Command> {
+    vec![
+        join::join(),
+        join::leave(),
+        pause::pause(),
+        pause::unpause(),
+        play::play(),
+        queue::playnext(),
+        skip::next(),
+        skip::prev(),
+        queue::queue(),
+        queue::remove(),
+        queue::insert(),
+    ]
+}
@@ -1,41 +1,33 @@
-use std::{future::Future, pin::Pin};
-
-use twilight_model::gateway::payload::incoming::MessageCreate;
-
-use crate::{parser::CommandWithData, State};
-
-async fn unpause_impl(s: State, m: MessageCreate, _c: CommandWithData) -> anyhow::Result<()> {
-    let lock = s.vcs.lock().await.clone();
-    if let Some(queue_lock) = lock.get(&m.guild_id.unwrap()) {
+use crate::{error::CommandError, parser::command};
+
+command! {
+    /// Resume the current song.
+    #[category = Music]
+    #[guild_only = true]
+    pub async fn unpause(s: State, ctx: Context) -> anyhow::Result<()> {
+        let lock = s.vcs.lock().await.clone();
+        let Some(queue_lock) = lock.get(&ctx.guild()?) else {
+            return Err(CommandError::NothingQueued.into());
+        };
         let queue = queue_lock.lock().await;
         queue.unpause()?;
-    }
-
-    Ok(())
-}
 
-pub fn unpause(
-    s: State,
-    m: MessageCreate,
-    c: CommandWithData,
-) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + 'static>> {
-    return (move |sc, mc, cc| Box::pin(unpause_impl(sc, mc, cc)))(s, m, c);
+        Ok(())
+    }
 }
 
-async fn pause_impl(s: State, m: MessageCreate, _c: CommandWithData) -> anyhow::Result<()> {
-    let vcs = s.vcs.lock().await.clone();
-    if let Some(queue_lock) = vcs.get(&m.guild_id.unwrap()) {
+command! {
+    /// Pause the current song.
+    #[category = Music]
+    #[guild_only = true]
+    pub async fn pause(s: State, ctx: Context) -> anyhow::Result<()> {
+        let vcs = s.vcs.lock().await.clone();
+        let Some(queue_lock) = vcs.get(&ctx.guild()?) else {
+            return Err(CommandError::NothingQueued.into());
+        };
         let queue = queue_lock.lock().await;
         queue.pause()?;
-    }
 
-    Ok(())
-}
-
-pub fn pause(
-    s: State,
-    m: MessageCreate,
-    c: CommandWithData,
-) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + 'static>> {
-    return (move |sc, mc, cc| Box::pin(pause_impl(sc, mc, cc)))(s, m, c);
+        Ok(())
+    }
 }
@@ -1,15 +1,22 @@
-use std::{future::Future, pin::Pin, sync::Arc};
+use std::sync::Arc;
 
-use songbird::input::YoutubeDl;
-use twilight_model::{
-    channel::message::AllowedMentions, gateway::payload::incoming::MessageCreate,
+use crate::{
+    error::CommandError,
+    music::Queue,
+    parser::{command, Rest},
 };
 
-use crate::{music::Queue, parser::CommandWithData, State};
-
-async fn queue_impl(s: State, m: MessageCreate, _c: CommandWithData) -> anyhow::Result<()> {
-    let vcs = s.vcs.lock().await.clone();
-    if let Some(queue_lock) = vcs.get(&m.guild_id.unwrap()) {
+command! {
+    /// Show the songs in the queue.
+    #[category = Music]
+    #[aliases = ["q", "np"]]
+    #[cooldown(Channel, 5)]
+    #[guild_only = true]
+    pub async fn queue(s: State, ctx: Context) -> anyhow::Result<()> {
+        let vcs = s.vcs.lock().await.clone();
+        let Some(queue_lock) = vcs.get(&ctx.guild()?) else {
+            return Err(CommandError::NothingQueued.into());
+        };
         let queue = queue_lock.lock().await;
         let content = queue.get_tracklist().await;
 
@@ -25,192 +32,89 @@ async fn queue_impl(s: State, m: MessageCreate, _c: CommandWithData) -> anyhow::
         }
         str += "```";
 
-        s.http
-            .create_message(m.channel_id)
-            .allowed_mentions(Some(&AllowedMentions::default()))
-            .content(&format!("{str}"))
-            .reply(m.id)
-            .await?;
-    } else {
-        return Ok(());
-    };
+        ctx.reply(&format!("{str}")).await?;
 
-    Ok(())
-}
-pub fn queue(
-    s: State,
-    m: MessageCreate,
-    c: CommandWithData,
-) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + 'static>> {
-    return (move |sc, mc, cc| Box::pin(queue_impl(sc, mc, cc)))(s, m, c);
+        Ok(())
+    }
 }
 
-async fn remove_impl(s: State, m: MessageCreate, c: CommandWithData) -> anyhow::Result<()> {
-    let mut vcs = s.vcs.lock().await.clone();
-    if let Some(queue_lock) = vcs.get_mut(&m.guild_id.unwrap()) {
+command! {
+    /// Remove the song at a position in the queue.
+    #[category = Music]
+    #[aliases = ["rm"]]
+    #[permissions = MANAGE_MESSAGES]
+    #[guild_only = true]
+    pub async fn remove(s: State, ctx: Context, index: u32) -> anyhow::Result<()> {
+        let mut vcs = s.vcs.lock().await.clone();
+        let Some(queue_lock) = vcs.get_mut(&ctx.guild()?) else {
+            return Err(CommandError::NothingQueued.into());
+        };
         let mut queue = queue_lock.lock().await;
-        let mut maybe_index = None;
-        if let Some(args) = c.arguments {
-            if !args.is_empty() {
-                maybe_index = args[0].clone().uint();
-            }
+        let content = if index == 0 {
+            String::from("There is nothing before the queue. You cannot remove nothing.")
+        } else if index as usize > queue.len() {
+            String::from("Add more songs if you want to remove something from after the queue.")
+        } else {
+            queue
+                .remove(Arc::clone(&s), ctx.guild()?, index as usize - 1)
+                .await?;
+            format!("Removed: {index} from the queue.")
         };
 
-        let mut content = String::from("You cannot remove nothing from the queue. Nice try dummy.");
-        if let Some(index) = maybe_index {
-            content = if index == 0 {
-                String::from("There is nothing before the queue. You cannot remove nothing.")
-            } else if index as usize > queue.len() {
-                String::from("Add more songs if you want to remove something from after the queue.")
-            } else {
-                queue
-                    .remove(Arc::clone(&s), m.guild_id.unwrap(), index as usize - 1)
-                    .await?;
-                format!("Removed: {index} from the queue.")
-            };
-        }
-
-        s.http
-            .create_message(m.channel_id)
-            .allowed_mentions(Some(&AllowedMentions::default()))
-            .content(&content)
-            .reply(m.id)
-            .await?;
+        ctx.reply(&content).await?;
+        Ok(())
     }
-    Ok(())
 }
 
-pub fn remove(
-    s: State,
-    m: MessageCreate,
-    c: CommandWithData,
-) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + 'static>> {
-    return (move |sc, mc, cc| Box::pin(remove_impl(sc, mc, cc)))(s, m, c);
-}
-
-async fn insert_impl(s: State, m: MessageCreate, c: CommandWithData) -> anyhow::Result<()> {
-    let mut vcs = s.vcs.lock().await.clone();
-    if let Some(queue_lock) = vcs.get_mut(&m.guild_id.unwrap()) {
-        let mut queue = queue_lock.lock().await;
-
-        let mut url = None;
-        if let Some(ref args) = c.arguments {
-            if args.len() > 1 {
-                url = Some({
-                    let mut iter = args.iter();
-                    iter.next();
-                    iter.map(|x| x.string().unwrap())
-                        .collect::<Vec<_>>()
-                        .join(" ")
-                });
-            }
-        };
-
-        let mut maybe_index = None;
-        if let Some(ref args) = c.arguments {
-            if !args.is_empty() {
-                maybe_index = args[0].clone().uint();
-            }
+command! {
+    /// Add a song at a position in the queue.
+    #[category = Music]
+    #[cooldown(User, 3)]
+    #[guild_only = true]
+    pub async fn insert(s: State, ctx: Context, index: u32, song: Rest) -> anyhow::Result<()> {
+        let mut vcs = s.vcs.lock().await.clone();
+        let Some(queue_lock) = vcs.get_mut(&ctx.guild()?) else {
+            return Err(CommandError::NothingQueued.into());
         };
+        ctx.defer().await?;
+        let mut queue = queue_lock.lock().await;
 
         let client = s.client.clone();
-        let src = url.clone().map(async |u| {
-            let mut search = YoutubeDl::new_search(client.clone(), u);
-            let res = search.search(Some(1)).await.unwrap().collect::<Vec<_>>();
-            let metadata = &res[0];
-            let url = metadata.source_url.clone().unwrap();
-
-            return YoutubeDl::new(client.clone(), url.clone());
-        });
-
-        let mut content = format!(
-            "You can't insert something at nothing. Perhaps you meant `insert {} {}`",
-            maybe_index.unwrap_or(queue.len() as u32 - 1),
-            url.clone().unwrap_or(String::from("<song here>"))
-        );
-        if let Some(index) = maybe_index {
-            content = if index == 0 {
-                String::from("There is no 0th position.")
-            } else if url.is_some() {
-                let s = src.unwrap().await;
-                let song = Queue::format_song(s.clone()).await;
-                queue.insert(s, index as usize - 1);
-                format!("Inserted: '{song}' at {index} in the queue")
-            } else {
-                format!("Insert what? Insert nothing? Not possible.")
-            }
-        }
+        let content = if index == 0 {
+            String::from("There is no 0th position.")
+        } else {
+            let (src, _) = Queue::search(client, song.0).await?;
+            let song = Queue::format_song(src.clone()).await;
+            queue.insert(src, index as usize - 1);
+            format!("Inserted: '{song}' at {index} in the queue")
+        };
 
-        s.http
-            .create_message(m.channel_id)
-            .allowed_mentions(Some(&AllowedMentions::default()))
-            .content(&content)
-            .reply(m.id)
-            .await?;
+        ctx.reply(&content).await?;
+        Ok(())
     }
-    Ok(())
-}
-
-pub fn insert(
-    s: State,
-    m: MessageCreate,
-    c: CommandWithData,
-) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + 'static>> {
-    return (move |sc, mc, cc| Box::pin(insert_impl(sc, mc, cc)))(s, m, c);
 }
 
-async fn playnext_impl(s: State, m: MessageCreate, c: CommandWithData) -> anyhow::Result<()> {
-    let mut vcs = s.vcs.lock().await.clone();
-    if let Some(queue_lock) = vcs.get_mut(&m.guild_id.unwrap()) {
-        let mut queue = queue_lock.lock().await;
-
-        let mut url = None;
-        if let Some(ref args) = c.arguments {
-            if !args.is_empty() {
-                url = args[0].clone().string();
-                url = Some(
-                    args.iter()
-                        .map(|x| x.string().unwrap())
-                        .collect::<Vec<_>>()
-                        .join(" "),
-                );
-            }
+command! {
+    /// Add a song to the queue right after the current one.
+    #[category = Music]
+    #[cooldown(User, 3)]
+    #[guild_only = true]
+    pub async fn playnext(s: State, ctx: Context, song: Rest) -> anyhow::Result<()> {
+        let mut vcs = s.vcs.lock().await.clone();
+        let Some(queue_lock) = vcs.get_mut(&ctx.guild()?) else {
+            return Err(CommandError::NothingQueued.into());
         };
+        ctx.defer().await?;
+        let mut queue = queue_lock.lock().await;
 
         let client = s.client.clone();
-        let src = url.clone().map(async |u| {
-            let mut search = YoutubeDl::new_search(client.clone(), u);
-            let res = search.search(Some(1)).await.unwrap().collect::<Vec<_>>();
-            let metadata = &res[0];
-            let url = metadata.source_url.clone().unwrap();
-
-            return YoutubeDl::new(client.clone(), url.clone());
-        });
-
-        let content = if url.is_some() {
-            let s = src.unwrap().await;
-            let song = Queue::format_song(s.clone()).await;
-            let pos = queue.pos();
-            queue.insert(s, pos + 1);
-            format!("Inserted: '{song}' at {} in the queue", pos + 2)
-        } else {
-            String::from("You can't play nothing.")
-        };
-
-        s.http
-            .create_message(m.channel_id)
-            .allowed_mentions(Some(&AllowedMentions::default()))
-            .content(&content)
-            .reply(m.id)
-            .await?;
+        let (src, _) = Queue::search(client, song.0).await?;
+        let song = Queue::format_song(src.clone()).await;
+        let pos = queue.pos();
+        queue.insert(src, pos + 1);
+        let content = format!("Inserted: '{song}' at {} in the queue", pos + 2);
+
+        ctx.reply(&content).await?;
+        Ok(())
     }
-    Ok(())
-}
-
-pub fn playnext(
-    s: State,
-    m: MessageCreate,
-    c: CommandWithData,
-) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + 'static>> {
-    return (move |sc, mc, cc| Box::pin(playnext_impl(sc, mc, cc)))(s, m, c);
 }
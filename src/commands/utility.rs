@@ -1,39 +1,24 @@
-use std::{future::Future, pin::Pin};
+use crate::parser::{command, Command, Rest};
 
-use twilight_model::{
-    channel::message::AllowedMentions, gateway::payload::incoming::MessageCreate,
-};
-
-use crate::{parser::CommandWithData, State};
-
-async fn ping_impl(s: State, m: MessageCreate, c: CommandWithData) -> anyhow::Result<()> {
-    let current_time = std::time::SystemTime::now()
-        .duration_since(std::time::SystemTime::UNIX_EPOCH)
-        .expect("time travel???????????????????????????")
-        .as_secs();
+/// The top level commands defined here, in the order `help` lists them.
+pub fn top_level() -> Vec<Command> {
+    vec![ping()]
+}
 
-    let mut content = None;
-    if let Some(args) = c.arguments {
-        if !args.is_empty() {
-            content = args[0].string();
-        }
-    }
+command! {
+    /// Check that the bot is alive, optionally echoing some text.
+    #[category = Utility]
+    pub async fn ping(_s: State, ctx: Context, text: Option<Rest>) -> anyhow::Result<()> {
+        let current_time = std::time::SystemTime::now()
+            .duration_since(std::time::SystemTime::UNIX_EPOCH)
+            .expect("time travel???????????????????????????")
+            .as_secs();
 
-    s.http
-        .create_message(m.channel_id)
-        .allowed_mentions(Some(&AllowedMentions::default()))
-        .content(&format!(
+        ctx.reply(&format!(
             "{} - processed <t:{current_time}:R>",
-            content.unwrap_or_else(|| String::from("Pong!"))
+            text.map_or_else(|| String::from("Pong!"), |t| t.0)
         ))
-        .reply(m.id)
         .await?;
-    Ok(())
-}
-pub fn ping(
-    s: State,
-    m: MessageCreate,
-    c: CommandWithData,
-) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + 'static>> {
-    Box::pin(ping_impl(s, m, c))
+        Ok(())
+    }
 }
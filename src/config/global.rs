@@ -0,0 +1,261 @@
+use std::{env, fmt::Display, fs, io::ErrorKind, path::PathBuf, str::FromStr, time::Duration};
+
+use anyhow::{anyhow, bail, Context};
+use serde::Deserialize;
+use tracing::level_filters::LevelFilter;
+use twilight_gateway::Intents;
+use twilight_model::id::{marker::UserMarker, Id};
+
+use super::servers::DEFAULT_PREFIX;
+
+/// Where the bot config is read from unless `CONFIG` says otherwise.
+const DEFAULT_PATH: &str = "config.toml";
+
+/// The bot config file as written, before environment overrides and validation.
+/// Every setting is optional.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct File {
+    owners: Vec<Id<UserMarker>>,
+    default_prefix: String,
+    data_dir: PathBuf,
+    storage: String,
+    config_grace_days: u64,
+    /// Gateway intent names such as `GUILD_MESSAGES`, every intent when left out.
+    intents: Option<Vec<String>>,
+    log_level: String,
+    cache: CacheFile,
+    music: MusicFile,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct CacheFile {
+    messages: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct MusicFile {
+    /// Seconds the bot stays in a voice channel with nobody listening.
+    idle_timeout: u64,
+    /// Seconds between checks for finished queues.
+    poll_interval: u64,
+}
+
+impl Default for File {
+    fn default() -> Self {
+        Self {
+            owners: vec![],
+            default_prefix: String::from(DEFAULT_PREFIX),
+            data_dir: PathBuf::from("data"),
+            storage: String::from("files"),
+            config_grace_days: 30,
+            intents: None,
+            log_level: String::from("info"),
+            cache: CacheFile::default(),
+            music: MusicFile::default(),
+        }
+    }
+}
+
+impl Default for CacheFile {
+    fn default() -> Self {
+        Self { messages: 10 }
+    }
+}
+
+impl Default for MusicFile {
+    fn default() -> Self {
+        Self {
+            idle_timeout: 60,
+            poll_interval: 1,
+        }
+    }
+}
+
+/// Settings for the bot as a whole, as opposed to the per server `ServerConfig`.
+/// Read from a TOML file, `config.toml` or whatever `CONFIG` points at, with each setting
+/// overridable by an environment variable.
+pub struct BotConfig {
+    /// Users who may use every command regardless of permissions and cooldowns.
+    pub owners: Vec<Id<UserMarker>>,
+    /// The prefix servers use unless they set their own.
+    pub default_prefix: String,
+    pub data_dir: PathBuf,
+    /// The storage backend, see `storage::Backend::open`.
+    pub storage: String,
+    /// How long the config of a server the bot was removed from is kept.
+    pub config_grace: Duration,
+    pub intents: Intents,
+    pub log_level: LevelFilter,
+    /// How many messages per channel are cached.
+    pub message_cache_size: usize,
+    /// How long the bot stays in a voice channel with nobody listening.
+    pub idle_timeout: Duration,
+    /// How often queues are checked for having finished.
+    pub poll_interval: Duration,
+}
+
+impl BotConfig {
+    /// Reads the config file and environment, failing with what is wrong and where.
+    /// A missing file is fine unless `CONFIG` names it.
+    pub fn load() -> anyhow::Result<Self> {
+        let (path, explicit) =
+            env::var("CONFIG").map_or((DEFAULT_PATH.into(), false), |p| (p, true));
+        let mut file = match fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text).with_context(|| format!("invalid config {path}"))?,
+            Err(e) if e.kind() == ErrorKind::NotFound && !explicit => File::default(),
+            Err(e) => return Err(e).with_context(|| format!("could not read config {path}")),
+        };
+
+        if let Ok(owners) = env::var("OWNERS") {
+            file.owners = split(&owners)
+                .map(|o| parse("OWNERS", o))
+                .collect::<anyhow::Result<_>>()?;
+        }
+        if let Ok(intents) = env::var("INTENTS") {
+            file.intents = Some(split(&intents).map(String::from).collect());
+        }
+        override_with("DEFAULT_PREFIX", &mut file.default_prefix)?;
+        override_with("DATA_DIR", &mut file.data_dir)?;
+        override_with("STORAGE", &mut file.storage)?;
+        override_with("CONFIG_GRACE_DAYS", &mut file.config_grace_days)?;
+        override_with("LOG_LEVEL", &mut file.log_level)?;
+        override_with("MESSAGE_CACHE_SIZE", &mut file.cache.messages)?;
+        override_with("MUSIC_IDLE_TIMEOUT", &mut file.music.idle_timeout)?;
+        override_with("MUSIC_POLL_INTERVAL", &mut file.music.poll_interval)?;
+
+        Self::validate(file)
+    }
+
+    fn validate(file: File) -> anyhow::Result<Self> {
+        let prefix = &file.default_prefix;
+        if prefix.is_empty() || prefix.contains(char::is_whitespace) {
+            bail!("default_prefix `{prefix}` must not be empty or contain spaces");
+        }
+        if file.music.poll_interval == 0 {
+            bail!("music.poll_interval must be at least 1 second");
+        }
+
+        let intents = match file.intents {
+            None => Intents::all(),
+            Some(names) => names.iter().try_fold(Intents::empty(), |intents, name| {
+                Intents::from_name(&name.to_uppercase())
+                    .map(|intent| intents | intent)
+                    .ok_or_else(|| anyhow!("`{name}` is not a gateway intent"))
+            })?,
+        };
+        // The cache and voice both depend on these.
+        let required = Intents::GUILDS | Intents::GUILD_VOICE_STATES;
+        if !intents.contains(required) {
+            bail!("intents must include GUILDS and GUILD_VOICE_STATES");
+        }
+
+        let log_level = file
+            .log_level
+            .parse()
+            .map_err(|_| anyhow!("`{}` is not a log level", file.log_level))?;
+
+        Ok(Self {
+            owners: file.owners,
+            default_prefix: file.default_prefix,
+            data_dir: file.data_dir,
+            storage: file.storage,
+            config_grace: Duration::from_secs(file.config_grace_days.saturating_mul(24 * 60 * 60)),
+            intents,
+            log_level,
+            message_cache_size: file.cache.messages,
+            idle_timeout: Duration::from_secs(file.music.idle_timeout),
+            poll_interval: Duration::from_secs(file.music.poll_interval),
+        })
+    }
+}
+
+/// The non-empty items of a comma separated list.
+fn split(list: &str) -> impl Iterator<Item = &str> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+fn parse<T: FromStr<Err: Display>>(var: &str, value: &str) -> anyhow::Result<T> {
+    value
+        .parse()
+        .map_err(|e| anyhow!("invalid {var} `{value}`: {e}"))
+}
+
+/// Replaces `setting` with the environment variable `var`, if it is set.
+fn override_with<T: FromStr<Err: Display>>(var: &str, setting: &mut T) -> anyhow::Result<()> {
+    if let Ok(value) = env::var(var) {
+        *setting = parse(var, &value)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use twilight_gateway::Intents;
+
+    use super::BotConfig;
+
+    /// Validates a config file, failing with the message `load` would give.
+    fn config(toml: &str) -> Result<BotConfig, String> {
+        toml::from_str(toml)
+            .map_err(|e| e.to_string())
+            .and_then(|file| BotConfig::validate(file).map_err(|e| e.to_string()))
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        let config = config("").unwrap();
+        assert_eq!(config.default_prefix, "~");
+        assert_eq!(config.intents, Intents::all());
+    }
+
+    #[test]
+    fn rejects_an_empty_default_prefix() {
+        assert!(config("default_prefix = ''").is_err());
+        assert!(config("default_prefix = '! '").is_err());
+    }
+
+    #[test]
+    fn rejects_a_zero_poll_interval() {
+        assert!(config("[music]\npoll_interval = 0").is_err());
+    }
+
+    #[test]
+    fn reads_intents_regardless_of_case() {
+        let config = config("intents = ['guilds', 'GUILD_VOICE_STATES']").unwrap();
+        assert_eq!(
+            config.intents,
+            Intents::GUILDS | Intents::GUILD_VOICE_STATES
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_intents() {
+        let Err(why) = config("intents = ['GUILDS', 'GUILD_VOICE_STATES', 'NOPE']") else {
+            panic!("an unknown intent was accepted");
+        };
+        assert!(why.contains("`NOPE` is not a gateway intent"), "{why}");
+    }
+
+    #[test]
+    fn requires_the_guilds_and_voice_state_intents() {
+        assert!(config("intents = ['GUILD_VOICE_STATES']").is_err());
+        assert!(config("intents = ['GUILDS']").is_err());
+    }
+
+    #[test]
+    fn rejects_an_unknown_log_level() {
+        assert!(config("log_level = 'loud'").is_err());
+        assert!(config("log_level = 'debug'").is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(config("prefix = '!'").is_err());
+        assert!(config("[music]\nvolume = 50").is_err());
+    }
+}
@@ -1 +1,4 @@
+pub mod global;
+pub mod portable;
+pub mod schema;
 pub mod servers;
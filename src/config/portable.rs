@@ -0,0 +1,273 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use twilight_model::id::{
+    marker::{ChannelMarker, EmojiMarker, MessageMarker, RoleMarker},
+    Id,
+};
+
+use super::servers::{CommandRules, ServerConfig};
+
+/// The part of a `ServerConfig` admins can export and import, laid out to be read and edited by
+/// hand. Reminders belong to members rather than the server and are left out.
+/// NOTE: `SqliteStorage` stores configs in this layout too, so settings should only be added,
+/// and only with a default, or existing rows stop loading.
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PortableConfig {
+    pub prefixes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_default_prefix: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ignore_prefix_case: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggest_commands: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_channel: Option<Id<ChannelMarker>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spam_channel: Option<Id<ChannelMarker>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mute_role: Option<Id<RoleMarker>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub significant_reaction_count: Option<u32>,
+
+    pub blacklisted_words: Vec<String>,
+    pub aliases: BTreeMap<String, String>,
+    pub auto_responses: BTreeMap<String, String>,
+    pub auto_reacts: BTreeMap<String, Id<EmojiMarker>>,
+    pub significant_reactions: Vec<SignificantReaction>,
+    pub reaction_roles: Vec<ReactionRole>,
+    pub command_rules: BTreeMap<String, CommandRules>,
+    pub category_rules: BTreeMap<String, CommandRules>,
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignificantReaction {
+    pub emoji: Id<EmojiMarker>,
+    pub channel: Id<ChannelMarker>,
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReactionRole {
+    pub message: Id<MessageMarker>,
+    pub emoji: Id<EmojiMarker>,
+    pub role: Id<RoleMarker>,
+}
+
+/// The file formats a config can be exported to and imported from.
+#[derive(Clone, Copy)]
+pub enum Format {
+    Json,
+    Toml,
+}
+
+impl Format {
+    /// The format a file is in, going by its extension.
+    pub fn from_filename(name: &str) -> Option<Self> {
+        let (_, extension) = name.rsplit_once('.')?;
+        match extension.to_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Toml => "toml",
+        }
+    }
+
+    pub fn encode(self, config: &PortableConfig) -> anyhow::Result<String> {
+        Ok(match self {
+            Self::Json => serde_json::to_string_pretty(config)?,
+            Self::Toml => toml::to_string_pretty(config)?,
+        })
+    }
+
+    /// Parses `text`, with an error message meant to be shown to whoever wrote it.
+    pub fn decode(self, text: &str) -> Result<PortableConfig, String> {
+        match self {
+            Self::Json => serde_json::from_str(text).map_err(|e| e.to_string()),
+            Self::Toml => toml::from_str(text).map_err(|e| e.message().to_owned()),
+        }
+    }
+}
+
+impl From<&ServerConfig> for PortableConfig {
+    fn from(config: &ServerConfig) -> Self {
+        let mut significant_reactions = config
+            .channels
+            .significant_reactions
+            .iter()
+            .flatten()
+            .map(|(&emoji, &channel)| SignificantReaction { emoji, channel })
+            .collect::<Vec<_>>();
+        significant_reactions.sort_unstable();
+        let mut reaction_roles = config
+            .reaction_roles
+            .iter()
+            .flatten()
+            .map(|(&(message, emoji), &role)| ReactionRole {
+                message,
+                emoji,
+                role,
+            })
+            .collect::<Vec<_>>();
+        reaction_roles.sort_unstable();
+
+        Self {
+            prefixes: config
+                .prefixes
+                .as_deref()
+                .map(<[String]>::to_vec)
+                .unwrap_or_default(),
+            keep_default_prefix: config.keep_default_prefix,
+            ignore_prefix_case: config.ignore_prefix_case,
+            suggest_commands: config.suggest_commands,
+            log_channel: config.channels.log,
+            spam_channel: config.channels.spam,
+            mute_role: config.mute_role,
+            significant_reaction_count: config.significant_reaction_count,
+            blacklisted_words: config
+                .blacklisted_words
+                .as_deref()
+                .map(<[String]>::to_vec)
+                .unwrap_or_default(),
+            aliases: sorted(config.aliases.as_ref()),
+            auto_responses: sorted(config.auto_responses.as_ref()),
+            auto_reacts: sorted(config.auto_reacts.as_ref()),
+            significant_reactions,
+            reaction_roles,
+            command_rules: sorted(config.command_rules.as_ref()),
+            category_rules: sorted(config.category_rules.as_ref()),
+        }
+    }
+}
+
+impl PortableConfig {
+    /// Replaces every setting of `config` with these, keeping its reminders.
+    /// Words, triggers and alias names are lowercased the way the commands setting them do.
+    pub fn apply(self, config: &mut ServerConfig) {
+        config.prefixes = (!self.prefixes.is_empty()).then(|| self.prefixes.into());
+        config.keep_default_prefix = self.keep_default_prefix;
+        config.ignore_prefix_case = self.ignore_prefix_case;
+        config.suggest_commands = self.suggest_commands;
+        config.channels.log = self.log_channel;
+        config.channels.spam = self.spam_channel;
+        config.mute_role = self.mute_role;
+        config.significant_reaction_count = self.significant_reaction_count;
+        let mut words = Vec::<String>::new();
+        for word in self.blacklisted_words {
+            let word = word.to_lowercase();
+            if !words.contains(&word) {
+                words.push(word);
+            }
+        }
+        config.blacklisted_words = (!words.is_empty()).then(|| words.into());
+        config.aliases = unsorted(lowercase_keys(self.aliases));
+        config.auto_responses = unsorted(lowercase_keys(self.auto_responses));
+        config.auto_reacts = unsorted(lowercase_keys(self.auto_reacts));
+        config.channels.significant_reactions = unsorted(
+            self.significant_reactions
+                .into_iter()
+                .map(|r| (r.emoji, r.channel))
+                .collect(),
+        );
+        config.reaction_roles = unsorted(
+            self.reaction_roles
+                .into_iter()
+                .map(|r| ((r.message, r.emoji), r.role))
+                .collect(),
+        );
+        config.command_rules = unsorted(self.command_rules);
+        config.category_rules = unsorted(self.category_rules);
+    }
+
+    /// The settings which differ between `self` and `new`, as `-` and `+` lines.
+    pub fn diff(&self, new: &Self) -> anyhow::Result<Vec<String>> {
+        let (Value::Object(old), Value::Object(mut new)) =
+            (serde_json::to_value(self)?, serde_json::to_value(new)?)
+        else {
+            anyhow::bail!("configs should serialize to objects");
+        };
+
+        let mut lines = vec![];
+        for (key, old) in old {
+            let new = new.remove(&key).unwrap_or(Value::Null);
+            if old != new {
+                lines.extend(line('-', &key, &old));
+                lines.extend(line('+', &key, &new));
+            }
+        }
+        // Settings which were unset before.
+        for (key, new) in new {
+            lines.extend(line('+', &key, &new));
+        }
+        Ok(lines)
+    }
+}
+
+/// A diff line for a setting, or nothing if it is unset or empty.
+fn line(sign: char, key: &str, value: &Value) -> Option<String> {
+    let empty = match value {
+        Value::Null => true,
+        Value::Array(a) => a.is_empty(),
+        Value::Object(o) => o.is_empty(),
+        _ => false,
+    };
+    (!empty).then(|| format!("{sign} {key}: {value}"))
+}
+
+fn sorted<K: Ord + Clone, V: Clone>(map: Option<&HashMap<K, V>>) -> BTreeMap<K, V> {
+    map.into_iter()
+        .flatten()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+fn lowercase_keys<V>(map: BTreeMap<String, V>) -> BTreeMap<String, V> {
+    map.into_iter()
+        .map(|(k, v)| (k.to_lowercase(), v))
+        .collect()
+}
+
+fn unsorted<K: Eq + std::hash::Hash, V>(map: BTreeMap<K, V>) -> Option<HashMap<K, V>> {
+    (!map.is_empty()).then(|| map.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::PortableConfig;
+    use crate::config::servers::ServerConfig;
+
+    #[test]
+    fn lowercases_words_and_triggers() {
+        let imported = PortableConfig {
+            blacklisted_words: vec![String::from("Heck"), String::from("heck")],
+            auto_responses: BTreeMap::from([(String::from("Ping"), String::from("Pong"))]),
+            ..PortableConfig::default()
+        };
+        let mut config = ServerConfig::new();
+        imported.apply(&mut config);
+        assert_eq!(
+            config.blacklisted_words.as_deref(),
+            Some(&[String::from("heck")][..])
+        );
+        assert_eq!(
+            config
+                .auto_responses
+                .unwrap()
+                .get("ping")
+                .map(String::as_str),
+            Some("Pong")
+        );
+    }
+}
@@ -0,0 +1,774 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+use serde::{
+    de::{self, DeserializeOwned, Visitor},
+    Deserialize, Deserializer,
+};
+use twilight_model::id::Id;
+
+use super::servers::{CommandRules, Reminder, ServerConfig};
+
+/// Marks a config written with a schema version envelope.
+/// Configs saved before versioning existed are bare bincode, which can never begin with these
+/// bytes since the first field of every `ServerConfig` layout is an `Option` tag (0 or 1).
+const MAGIC: &[u8; 4] = b"CTAC";
+
+/// The schema version of the current `ServerConfig` layout.
+/// Bump this whenever a field is added, removed or reordered in `ServerConfig` or `ChannelSet`,
+/// mirror the new layout in a module below, and add a step to `upgrade`.
+pub const CURRENT_VERSION: u32 = 7;
+
+/// An `Id` as stored, which is as a string.
+/// `Id` can only be deserialized through `deserialize_any`, which bincode does not support, so
+/// the layouts below read ids through this instead and configs are never decoded into
+/// `ServerConfig` directly.
+pub struct StoredId<T>(Id<T>);
+
+impl<T> PartialEq for StoredId<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T> Eq for StoredId<T> {}
+
+impl<T> Hash for StoredId<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl<'de, T> Deserialize<'de> for StoredId<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct IdVisitor<T>(PhantomData<T>);
+
+        impl<T> Visitor<'_> for IdVisitor<T> {
+            type Value = StoredId<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a snowflake as a string")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                v.parse().map(StoredId).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(IdVisitor(PhantomData))
+    }
+}
+
+/// The layout used by schema versions 0 (before configs carried a version) and 1.
+mod v1 {
+    use std::{collections::HashMap, time::Duration};
+
+    use serde::Deserialize;
+    use twilight_model::id::marker::{
+        ChannelMarker, EmojiMarker, MessageMarker, RoleMarker, UserMarker,
+    };
+
+    use super::StoredId as Id;
+
+    type OptionId<T> = Option<Id<T>>;
+    type OptionMap<K, V> = Option<HashMap<K, V>>;
+
+    #[derive(Deserialize)]
+    pub struct Reminder {
+        pub begin: Duration,
+        pub end: Duration,
+        pub message: String,
+    }
+
+    #[derive(Deserialize)]
+    pub struct ChannelSet {
+        pub log: OptionId<ChannelMarker>,
+        pub spam: OptionId<ChannelMarker>,
+
+        pub significant_reactions: OptionMap<Id<EmojiMarker>, Id<ChannelMarker>>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct ServerConfig {
+        pub prefix: Option<String>,
+
+        pub channels: ChannelSet,
+
+        pub reaction_roles: OptionMap<(Id<MessageMarker>, Id<EmojiMarker>), Id<RoleMarker>>,
+
+        pub reminders: OptionMap<Id<UserMarker>, Vec<Reminder>>,
+
+        pub mute_role: OptionId<RoleMarker>,
+        pub blacklisted_words: Option<Box<[String]>>,
+
+        pub auto_responses: OptionMap<String, String>,
+        pub auto_reacts: OptionMap<String, Id<EmojiMarker>>,
+
+        pub significant_reaction_count: Option<u32>,
+    }
+}
+
+/// The layout used by schema version 2, which added guild aliases.
+mod v2 {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+    use twilight_model::id::marker::{EmojiMarker, MessageMarker, RoleMarker, UserMarker};
+
+    use super::StoredId as Id;
+
+    pub use super::v1::{ChannelSet, Reminder};
+
+    type OptionId<T> = Option<Id<T>>;
+    type OptionMap<K, V> = Option<HashMap<K, V>>;
+
+    #[derive(Deserialize)]
+    pub struct ServerConfig {
+        pub prefix: Option<String>,
+
+        pub channels: ChannelSet,
+
+        pub reaction_roles: OptionMap<(Id<MessageMarker>, Id<EmojiMarker>), Id<RoleMarker>>,
+
+        pub reminders: OptionMap<Id<UserMarker>, Vec<Reminder>>,
+
+        pub mute_role: OptionId<RoleMarker>,
+        pub blacklisted_words: Option<Box<[String]>>,
+
+        pub auto_responses: OptionMap<String, String>,
+        pub auto_reacts: OptionMap<String, Id<EmojiMarker>>,
+
+        pub significant_reaction_count: Option<u32>,
+
+        pub aliases: OptionMap<String, String>,
+    }
+
+    impl From<super::v1::ServerConfig> for ServerConfig {
+        fn from(old: super::v1::ServerConfig) -> Self {
+            Self {
+                prefix: old.prefix,
+                channels: old.channels,
+                reaction_roles: old.reaction_roles,
+                reminders: old.reminders,
+                mute_role: old.mute_role,
+                blacklisted_words: old.blacklisted_words,
+                auto_responses: old.auto_responses,
+                auto_reacts: old.auto_reacts,
+                significant_reaction_count: old.significant_reaction_count,
+                aliases: None,
+            }
+        }
+    }
+}
+
+/// The layout used by schema version 3, which added the suggestions toggle.
+mod v3 {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+    use twilight_model::id::marker::{EmojiMarker, MessageMarker, RoleMarker, UserMarker};
+
+    use super::StoredId as Id;
+
+    pub use super::v1::{ChannelSet, Reminder};
+
+    type OptionId<T> = Option<Id<T>>;
+    type OptionMap<K, V> = Option<HashMap<K, V>>;
+
+    #[derive(Deserialize)]
+    pub struct ServerConfig {
+        pub prefix: Option<String>,
+
+        pub channels: ChannelSet,
+
+        pub reaction_roles: OptionMap<(Id<MessageMarker>, Id<EmojiMarker>), Id<RoleMarker>>,
+
+        pub reminders: OptionMap<Id<UserMarker>, Vec<Reminder>>,
+
+        pub mute_role: OptionId<RoleMarker>,
+        pub blacklisted_words: Option<Box<[String]>>,
+
+        pub auto_responses: OptionMap<String, String>,
+        pub auto_reacts: OptionMap<String, Id<EmojiMarker>>,
+
+        pub significant_reaction_count: Option<u32>,
+
+        pub aliases: OptionMap<String, String>,
+
+        pub suggest_commands: Option<bool>,
+    }
+
+    impl From<super::v2::ServerConfig> for ServerConfig {
+        fn from(old: super::v2::ServerConfig) -> Self {
+            Self {
+                prefix: old.prefix,
+                channels: old.channels,
+                reaction_roles: old.reaction_roles,
+                reminders: old.reminders,
+                mute_role: old.mute_role,
+                blacklisted_words: old.blacklisted_words,
+                auto_responses: old.auto_responses,
+                auto_reacts: old.auto_reacts,
+                significant_reaction_count: old.significant_reaction_count,
+                aliases: old.aliases,
+                suggest_commands: None,
+            }
+        }
+    }
+}
+
+/// The layout used by schema version 4, which replaced the single prefix with a list.
+mod v4 {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+    use twilight_model::id::marker::{EmojiMarker, MessageMarker, RoleMarker, UserMarker};
+
+    use super::StoredId as Id;
+
+    pub use super::v1::{ChannelSet, Reminder};
+
+    type OptionId<T> = Option<Id<T>>;
+    type OptionMap<K, V> = Option<HashMap<K, V>>;
+
+    #[derive(Deserialize)]
+    pub struct ServerConfig {
+        pub prefixes: Option<Box<[String]>>,
+        pub ignore_prefix_case: Option<bool>,
+
+        pub channels: ChannelSet,
+
+        pub reaction_roles: OptionMap<(Id<MessageMarker>, Id<EmojiMarker>), Id<RoleMarker>>,
+
+        pub reminders: OptionMap<Id<UserMarker>, Vec<Reminder>>,
+
+        pub mute_role: OptionId<RoleMarker>,
+        pub blacklisted_words: Option<Box<[String]>>,
+
+        pub auto_responses: OptionMap<String, String>,
+        pub auto_reacts: OptionMap<String, Id<EmojiMarker>>,
+
+        pub significant_reaction_count: Option<u32>,
+
+        pub aliases: OptionMap<String, String>,
+
+        pub suggest_commands: Option<bool>,
+    }
+
+    impl From<super::v3::ServerConfig> for ServerConfig {
+        fn from(old: super::v3::ServerConfig) -> Self {
+            Self {
+                prefixes: old.prefix.map(|pfx| Box::new([pfx]) as Box<[String]>),
+                ignore_prefix_case: None,
+                channels: old.channels,
+                reaction_roles: old.reaction_roles,
+                reminders: old.reminders,
+                mute_role: old.mute_role,
+                blacklisted_words: old.blacklisted_words,
+                auto_responses: old.auto_responses,
+                auto_reacts: old.auto_reacts,
+                significant_reaction_count: old.significant_reaction_count,
+                aliases: old.aliases,
+                suggest_commands: old.suggest_commands,
+            }
+        }
+    }
+}
+
+/// The layout used by schema version 5, which added command and category rules.
+mod v5 {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+    use twilight_model::id::marker::{
+        ChannelMarker, EmojiMarker, MessageMarker, RoleMarker, UserMarker,
+    };
+
+    use super::StoredId as Id;
+
+    pub use super::v1::{ChannelSet, Reminder};
+
+    type OptionId<T> = Option<Id<T>>;
+    type OptionMap<K, V> = Option<HashMap<K, V>>;
+
+    #[derive(Deserialize)]
+    pub struct CommandRules {
+        pub disabled: bool,
+
+        pub allowed_channels: Vec<Id<ChannelMarker>>,
+        pub denied_channels: Vec<Id<ChannelMarker>>,
+
+        pub allowed_roles: Vec<Id<RoleMarker>>,
+        pub denied_roles: Vec<Id<RoleMarker>>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct ServerConfig {
+        pub prefixes: Option<Box<[String]>>,
+        pub ignore_prefix_case: Option<bool>,
+
+        pub channels: ChannelSet,
+
+        pub reaction_roles: OptionMap<(Id<MessageMarker>, Id<EmojiMarker>), Id<RoleMarker>>,
+
+        pub reminders: OptionMap<Id<UserMarker>, Vec<Reminder>>,
+
+        pub mute_role: OptionId<RoleMarker>,
+        pub blacklisted_words: Option<Box<[String]>>,
+
+        pub auto_responses: OptionMap<String, String>,
+        pub auto_reacts: OptionMap<String, Id<EmojiMarker>>,
+
+        pub significant_reaction_count: Option<u32>,
+
+        pub aliases: OptionMap<String, String>,
+
+        pub suggest_commands: Option<bool>,
+
+        pub command_rules: OptionMap<String, CommandRules>,
+        pub category_rules: OptionMap<String, CommandRules>,
+    }
+
+    impl From<super::v4::ServerConfig> for ServerConfig {
+        fn from(old: super::v4::ServerConfig) -> Self {
+            Self {
+                prefixes: old.prefixes,
+                ignore_prefix_case: old.ignore_prefix_case,
+                channels: old.channels,
+                reaction_roles: old.reaction_roles,
+                reminders: old.reminders,
+                mute_role: old.mute_role,
+                blacklisted_words: old.blacklisted_words,
+                auto_responses: old.auto_responses,
+                auto_reacts: old.auto_reacts,
+                significant_reaction_count: old.significant_reaction_count,
+                aliases: old.aliases,
+                suggest_commands: old.suggest_commands,
+                command_rules: None,
+                category_rules: None,
+            }
+        }
+    }
+}
+
+/// The layout used by schema version 6, which added when the bot was removed from the server.
+mod v6 {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+    use twilight_model::id::marker::{EmojiMarker, MessageMarker, RoleMarker, UserMarker};
+
+    use super::StoredId as Id;
+
+    pub use super::v5::{ChannelSet, CommandRules, Reminder};
+
+    type OptionId<T> = Option<Id<T>>;
+    type OptionMap<K, V> = Option<HashMap<K, V>>;
+
+    #[derive(Deserialize)]
+    pub struct ServerConfig {
+        pub prefixes: Option<Box<[String]>>,
+        pub ignore_prefix_case: Option<bool>,
+
+        pub channels: ChannelSet,
+
+        pub reaction_roles: OptionMap<(Id<MessageMarker>, Id<EmojiMarker>), Id<RoleMarker>>,
+
+        pub reminders: OptionMap<Id<UserMarker>, Vec<Reminder>>,
+
+        pub mute_role: OptionId<RoleMarker>,
+        pub blacklisted_words: Option<Box<[String]>>,
+
+        pub auto_responses: OptionMap<String, String>,
+        pub auto_reacts: OptionMap<String, Id<EmojiMarker>>,
+
+        pub significant_reaction_count: Option<u32>,
+
+        pub aliases: OptionMap<String, String>,
+
+        pub suggest_commands: Option<bool>,
+
+        pub command_rules: OptionMap<String, CommandRules>,
+        pub category_rules: OptionMap<String, CommandRules>,
+
+        pub left_at: Option<u64>,
+    }
+
+    impl From<super::v5::ServerConfig> for ServerConfig {
+        fn from(old: super::v5::ServerConfig) -> Self {
+            Self {
+                prefixes: old.prefixes,
+                ignore_prefix_case: old.ignore_prefix_case,
+                channels: old.channels,
+                reaction_roles: old.reaction_roles,
+                reminders: old.reminders,
+                mute_role: old.mute_role,
+                blacklisted_words: old.blacklisted_words,
+                auto_responses: old.auto_responses,
+                auto_reacts: old.auto_reacts,
+                significant_reaction_count: old.significant_reaction_count,
+                aliases: old.aliases,
+                suggest_commands: old.suggest_commands,
+                command_rules: old.command_rules,
+                category_rules: old.category_rules,
+                left_at: None,
+            }
+        }
+    }
+}
+
+/// The layout used by schema version 7, which stopped storing the default prefix alongside the
+/// prefixes a server added.
+mod v7 {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+    use twilight_model::id::marker::{EmojiMarker, MessageMarker, RoleMarker, UserMarker};
+
+    use super::StoredId as Id;
+
+    pub use super::v6::{ChannelSet, CommandRules, Reminder};
+
+    type OptionId<T> = Option<Id<T>>;
+    type OptionMap<K, V> = Option<HashMap<K, V>>;
+
+    #[derive(Deserialize)]
+    pub struct ServerConfig {
+        pub prefixes: Option<Box<[String]>>,
+        pub keep_default_prefix: Option<bool>,
+        pub ignore_prefix_case: Option<bool>,
+
+        pub channels: ChannelSet,
+
+        pub reaction_roles: OptionMap<(Id<MessageMarker>, Id<EmojiMarker>), Id<RoleMarker>>,
+
+        pub reminders: OptionMap<Id<UserMarker>, Vec<Reminder>>,
+
+        pub mute_role: OptionId<RoleMarker>,
+        pub blacklisted_words: Option<Box<[String]>>,
+
+        pub auto_responses: OptionMap<String, String>,
+        pub auto_reacts: OptionMap<String, Id<EmojiMarker>>,
+
+        pub significant_reaction_count: Option<u32>,
+
+        pub aliases: OptionMap<String, String>,
+
+        pub suggest_commands: Option<bool>,
+
+        pub command_rules: OptionMap<String, CommandRules>,
+        pub category_rules: OptionMap<String, CommandRules>,
+
+        pub left_at: Option<u64>,
+    }
+
+    impl From<super::v6::ServerConfig> for ServerConfig {
+        fn from(old: super::v6::ServerConfig) -> Self {
+            Self {
+                // A stored list used to be every prefix, including the default if it was kept.
+                keep_default_prefix: old.prefixes.is_some().then_some(false),
+                prefixes: old.prefixes,
+                ignore_prefix_case: old.ignore_prefix_case,
+                channels: old.channels,
+                reaction_roles: old.reaction_roles,
+                reminders: old.reminders,
+                mute_role: old.mute_role,
+                blacklisted_words: old.blacklisted_words,
+                auto_responses: old.auto_responses,
+                auto_reacts: old.auto_reacts,
+                significant_reaction_count: old.significant_reaction_count,
+                aliases: old.aliases,
+                suggest_commands: old.suggest_commands,
+                command_rules: old.command_rules,
+                category_rules: old.category_rules,
+                left_at: old.left_at,
+            }
+        }
+    }
+}
+
+impl From<v7::ServerConfig> for ServerConfig {
+    fn from(old: v7::ServerConfig) -> Self {
+        let mut config = Self::new();
+        config.prefixes = old.prefixes;
+        config.keep_default_prefix = old.keep_default_prefix;
+        config.ignore_prefix_case = old.ignore_prefix_case;
+        config.channels.log = old.channels.log.map(|c| c.0);
+        config.channels.spam = old.channels.spam.map(|c| c.0);
+        config.channels.significant_reactions = old.channels.significant_reactions.map(|r| {
+            r.into_iter()
+                .map(|(emoji, channel)| (emoji.0, channel.0))
+                .collect()
+        });
+        config.reaction_roles = old.reaction_roles.map(|r| {
+            r.into_iter()
+                .map(|((message, emoji), role)| ((message.0, emoji.0), role.0))
+                .collect()
+        });
+        config.reminders = old.reminders.map(|reminders| {
+            reminders
+                .into_iter()
+                .map(|(user, list)| {
+                    let list = list
+                        .into_iter()
+                        .map(|r| Reminder {
+                            begin: r.begin,
+                            end: r.end,
+                            message: r.message,
+                        })
+                        .collect();
+                    (user.0, list)
+                })
+                .collect()
+        });
+        config.mute_role = old.mute_role.map(|r| r.0);
+        config.blacklisted_words = old.blacklisted_words;
+        config.auto_responses = old.auto_responses;
+        config.auto_reacts = old.auto_reacts.map(|r| {
+            r.into_iter()
+                .map(|(trigger, emoji)| (trigger, emoji.0))
+                .collect()
+        });
+        config.significant_reaction_count = old.significant_reaction_count;
+        config.aliases = old.aliases;
+        config.suggest_commands = old.suggest_commands;
+        config.command_rules = old.command_rules.map(upgrade_rules);
+        config.category_rules = old.category_rules.map(upgrade_rules);
+        config.left_at = old.left_at;
+        config
+    }
+}
+
+fn ids<T>(ids: Vec<StoredId<T>>) -> Vec<Id<T>> {
+    ids.into_iter().map(|id| id.0).collect()
+}
+
+fn upgrade_rules(rules: HashMap<String, v7::CommandRules>) -> HashMap<String, CommandRules> {
+    rules
+        .into_iter()
+        .map(|(name, r)| {
+            let rules = CommandRules {
+                disabled: r.disabled,
+                allowed_channels: ids(r.allowed_channels),
+                denied_channels: ids(r.denied_channels),
+                allowed_roles: ids(r.allowed_roles),
+                denied_roles: ids(r.denied_roles),
+            };
+            (name, rules)
+        })
+        .collect()
+}
+
+fn decode_exact<T: DeserializeOwned>(data: &[u8]) -> anyhow::Result<T> {
+    let (value, read) =
+        bincode::serde::decode_from_slice::<T, _>(data, bincode::config::standard())?;
+    if read != data.len() {
+        anyhow::bail!(
+            "{} trailing bytes after the config, the file is likely corrupt",
+            data.len() - read
+        );
+    }
+    Ok(value)
+}
+
+/// Decodes a payload of the given schema version, migrating it step by step up to the current layout.
+fn upgrade(version: u32, payload: &[u8]) -> anyhow::Result<ServerConfig> {
+    let old = match version {
+        0 | 1 => {
+            let old = decode_exact::<v1::ServerConfig>(payload)?;
+            let old = v3::ServerConfig::from(v2::ServerConfig::from(old));
+            let old = v5::ServerConfig::from(v4::ServerConfig::from(old));
+            v7::ServerConfig::from(v6::ServerConfig::from(old))
+        }
+        2 => {
+            let old = v3::ServerConfig::from(decode_exact::<v2::ServerConfig>(payload)?);
+            let old = v5::ServerConfig::from(v4::ServerConfig::from(old));
+            v7::ServerConfig::from(v6::ServerConfig::from(old))
+        }
+        3 => {
+            let old = v4::ServerConfig::from(decode_exact::<v3::ServerConfig>(payload)?);
+            v7::ServerConfig::from(v6::ServerConfig::from(v5::ServerConfig::from(old)))
+        }
+        4 => {
+            let old = v5::ServerConfig::from(decode_exact::<v4::ServerConfig>(payload)?);
+            v7::ServerConfig::from(v6::ServerConfig::from(old))
+        }
+        5 => {
+            let old = v6::ServerConfig::from(decode_exact::<v5::ServerConfig>(payload)?);
+            v7::ServerConfig::from(old)
+        }
+        6 => v7::ServerConfig::from(decode_exact::<v6::ServerConfig>(payload)?),
+        CURRENT_VERSION => decode_exact::<v7::ServerConfig>(payload)?,
+        v => anyhow::bail!(
+            "config has schema version {v}, but this build only understands up to {CURRENT_VERSION}"
+        ),
+    };
+    Ok(old.into())
+}
+
+/// Encodes a config with the current schema version.
+pub fn encode(config: &ServerConfig) -> anyhow::Result<Vec<u8>> {
+    let mut data = Vec::from(*MAGIC);
+    data.extend_from_slice(&CURRENT_VERSION.to_le_bytes());
+    bincode::serde::encode_into_std_write(config, &mut data, bincode::config::standard())?;
+    Ok(data)
+}
+
+/// Decodes a config of any known schema version, including unversioned configs.
+pub fn decode(data: &[u8]) -> anyhow::Result<ServerConfig> {
+    let Some(rest) = data.strip_prefix(MAGIC) else {
+        return upgrade(0, data);
+    };
+    let Some((version, payload)) = rest.split_first_chunk::<4>() else {
+        anyhow::bail!("config header is truncated");
+    };
+    upgrade(u32::from_le_bytes(*version), payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use twilight_model::id::Id;
+
+    use super::{decode, encode, ServerConfig};
+    use crate::config::servers::DEFAULT_PREFIX;
+
+    /// Configs saved by each schema version, holding the settings checked in `check_common`.
+    const V0: &[u8] = include_bytes!("fixtures/v0.bin");
+    const V1: &[u8] = include_bytes!("fixtures/v1.bin");
+    const V2: &[u8] = include_bytes!("fixtures/v2.bin");
+    const V3: &[u8] = include_bytes!("fixtures/v3.bin");
+    const V4: &[u8] = include_bytes!("fixtures/v4.bin");
+    const V5: &[u8] = include_bytes!("fixtures/v5.bin");
+    const V6: &[u8] = include_bytes!("fixtures/v6.bin");
+
+    /// The settings every fixture has, which each migration has to carry over.
+    fn check_common(config: &ServerConfig) {
+        assert_eq!(config.channels.log, Some(Id::new(100)));
+        assert_eq!(config.channels.spam, None);
+        let significant = config.channels.significant_reactions.as_ref().unwrap();
+        assert_eq!(significant.get(&Id::new(300)), Some(&Id::new(101)));
+        let roles = config.reaction_roles.as_ref().unwrap();
+        assert_eq!(
+            roles.get(&(Id::new(400), Id::new(300))),
+            Some(&Id::new(200))
+        );
+        assert!(config.reminders.is_none());
+        assert_eq!(config.mute_role, Some(Id::new(201)));
+        assert_eq!(
+            config.blacklisted_words.as_deref(),
+            Some(&[String::from("heck")][..])
+        );
+        let responses = config.auto_responses.as_ref().unwrap();
+        assert_eq!(responses.get("hi").map(String::as_str), Some("hello"));
+        assert!(config.auto_reacts.is_none());
+        assert_eq!(config.significant_reaction_count, Some(3));
+    }
+
+    #[test]
+    fn decodes_unversioned() {
+        let config = decode(V0).unwrap();
+        check_common(&config);
+        assert_eq!(config.prefixes(DEFAULT_PREFIX), ["!"]);
+        assert!(config.aliases.is_none());
+        assert!(config.suggests_commands());
+    }
+
+    #[test]
+    fn decodes_v1() {
+        let config = decode(V1).unwrap();
+        check_common(&config);
+        assert_eq!(config.prefixes(DEFAULT_PREFIX), ["!"]);
+        assert!(config.aliases.is_none());
+    }
+
+    #[test]
+    fn decodes_v2() {
+        let config = decode(V2).unwrap();
+        check_common(&config);
+        assert_eq!(config.alias("lofi").map(String::as_str), Some("play lofi"));
+        assert_eq!(config.suggest_commands, None);
+    }
+
+    #[test]
+    fn decodes_v3_single_prefix_into_list() {
+        let config = decode(V3).unwrap();
+        check_common(&config);
+        assert_eq!(config.prefixes(DEFAULT_PREFIX), ["!"]);
+        assert!(!config.ignores_prefix_case());
+        assert_eq!(config.suggest_commands, Some(false));
+        assert_eq!(config.alias("lofi").map(String::as_str), Some("play lofi"));
+    }
+
+    #[test]
+    fn decodes_v4() {
+        let config = decode(V4).unwrap();
+        check_common(&config);
+        assert_eq!(config.prefixes(DEFAULT_PREFIX), ["!", "?"]);
+        assert!(config.ignores_prefix_case());
+        assert!(config.command_rules.is_none());
+        assert!(config.category_rules.is_none());
+    }
+
+    #[test]
+    fn decodes_v5() {
+        let config = decode(V5).unwrap();
+        check_common(&config);
+        assert_eq!(config.prefixes(DEFAULT_PREFIX), ["!", "?"]);
+        let rules = &config.command_rules.as_ref().unwrap()["play"];
+        assert!(!rules.disabled);
+        assert_eq!(rules.allowed_channels, [Id::new(101)]);
+        assert!(rules.denied_channels.is_empty());
+        assert!(rules.allowed_roles.is_empty());
+        assert_eq!(rules.denied_roles, [Id::new(201)]);
+        assert!(config.category_rules.is_none());
+    }
+
+    #[test]
+    fn decodes_v6_prefixes_without_the_default() {
+        let config = decode(V6).unwrap();
+        check_common(&config);
+        assert_eq!(config.prefixes(DEFAULT_PREFIX), ["!", "?"]);
+        assert_eq!(config.prefixes("$"), ["!", "?"]);
+        assert!(config.command_rules.as_ref().unwrap().contains_key("play"));
+        assert!(config.left_at.is_some());
+    }
+
+    #[test]
+    fn round_trips_current_version() {
+        let mut config = decode(V6).unwrap();
+        config.reset_prefixes();
+        config.add_prefix("?", DEFAULT_PREFIX);
+        let config = decode(&encode(&config).unwrap()).unwrap();
+        check_common(&config);
+        assert_eq!(config.prefixes("$"), ["$", "?"]);
+        assert!(config.command_rules.as_ref().unwrap().contains_key("play"));
+        assert!(config.left_at.is_some());
+    }
+
+    #[test]
+    fn rejects_truncated_header() {
+        let error = decode(b"CTAC\x01\x00").err().unwrap();
+        assert!(error.to_string().contains("truncated"));
+    }
+
+    #[test]
+    fn rejects_unknown_version() {
+        let mut data = Vec::from(&b"CTAC"[..]);
+        data.extend_from_slice(&99u32.to_le_bytes());
+        data.extend_from_slice(&V1[8..]);
+        let error = decode(&data).err().unwrap();
+        assert!(error.to_string().contains("schema version 99"));
+    }
+
+    #[test]
+    fn rejects_trailing_bytes() {
+        let mut data = V1.to_vec();
+        data.push(0);
+        let error = decode(&data).err().unwrap();
+        assert!(error.to_string().contains("1 trailing bytes"));
+    }
+}
@@ -1,4 +1,7 @@
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use serde::{Deserialize, Serialize};
 
@@ -10,6 +13,9 @@ use twilight_model::id::{
 type OptionId<T> = Option<Id<T>>;
 type OptionMap<K, V> = Option<HashMap<K, V>>;
 
+/// The prefix used when neither the server nor the bot config sets one.
+pub const DEFAULT_PREFIX: &str = "~";
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Reminder {
     pub begin: Duration,
@@ -25,14 +31,89 @@ pub struct ChannelSet {
     pub significant_reactions: OptionMap<Id<EmojiMarker>, Id<ChannelMarker>>,
 }
 
+/// Where a command, or every command in a category, may be used and by whom.
+/// Empty lists place no restriction.
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandRules {
+    pub disabled: bool,
+
+    /// When not empty, the only channels the command can be used in.
+    pub allowed_channels: Vec<Id<ChannelMarker>>,
+    pub denied_channels: Vec<Id<ChannelMarker>>,
+
+    /// When not empty, the command can only be used by members with one of these roles.
+    pub allowed_roles: Vec<Id<RoleMarker>>,
+    pub denied_roles: Vec<Id<RoleMarker>>,
+}
+
+impl CommandRules {
+    pub fn is_empty(&self) -> bool {
+        !self.disabled
+            && self.allowed_channels.is_empty()
+            && self.denied_channels.is_empty()
+            && self.allowed_roles.is_empty()
+            && self.denied_roles.is_empty()
+    }
+
+    /// Why `what`, e.g. "`jump`" or "music commands", may not be used in `channel` by a member
+    /// with `roles`, or `None` if it may.
+    pub fn denial(
+        &self,
+        what: &str,
+        channel: Id<ChannelMarker>,
+        roles: &[Id<RoleMarker>],
+    ) -> Option<String> {
+        if self.disabled {
+            return Some(format!("This server has disabled {what}."));
+        }
+        if self.denied_channels.contains(&channel) {
+            return Some(format!("You can't use {what} in this channel."));
+        }
+        if !self.allowed_channels.is_empty() && !self.allowed_channels.contains(&channel) {
+            let channels = self
+                .allowed_channels
+                .iter()
+                .map(|c| format!("<#{c}>"))
+                .collect::<Vec<_>>();
+            return Some(format!(
+                "You can only use {what} in {}.",
+                channels.join(", ")
+            ));
+        }
+        if roles.iter().any(|r| self.denied_roles.contains(r)) {
+            return Some(format!("Your roles don't allow you to use {what}."));
+        }
+        if !self.allowed_roles.is_empty() && !roles.iter().any(|r| self.allowed_roles.contains(r)) {
+            let roles = self
+                .allowed_roles
+                .iter()
+                .map(|r| format!("<@&{r}>"))
+                .collect::<Vec<_>>();
+            return Some(format!(
+                "You need one of {} to use {what}.",
+                roles.join(", ")
+            ));
+        }
+        None
+    }
+}
+
 /// `ServerConfig` represents the configuration for any given Discord guild, and contains many settings
 /// which an admin may configure.
 /// NOTE: Any value that is None disables related behaviors
 /// i.e: if `significant_reaction_count` is None then this bot will never post significant reactions,
 /// The same applies if `channels.significant_reactions` is None.
+/// NOTE: Changing the layout of this struct (or `ChannelSet`) requires a new schema version,
+/// see `config::schema`.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
-    prefix: Option<String>,
+    /// The prefixes this server set or added, besides the bot's default prefix when it is kept.
+    pub(super) prefixes: Option<Box<[String]>>,
+    /// Whether the bot's default prefix works too, on until a prefix is set or the default removed.
+    /// The default itself is never stored, so a change to it reaches every server which kept it.
+    pub(super) keep_default_prefix: Option<bool>,
+    /// Whether prefixes and command names match regardless of case, off by default.
+    pub ignore_prefix_case: Option<bool>,
 
     pub channels: ChannelSet,
 
@@ -47,12 +128,30 @@ pub struct ServerConfig {
     pub auto_reacts: OptionMap<String, Id<EmojiMarker>>,
 
     pub significant_reaction_count: Option<u32>,
+
+    /// Guild specific command aliases, mapping a name to the command line it expands to,
+    /// e.g. `lofi` -> `play lofi hip hop radio`.
+    pub aliases: OptionMap<String, String>,
+
+    /// Whether unknown commands are answered with the closest matching commands, on by default.
+    pub suggest_commands: Option<bool>,
+
+    /// Rules for top level commands, keyed by name.
+    pub command_rules: OptionMap<String, CommandRules>,
+    /// Rules for every command in a category, keyed by the category's lowercase name.
+    pub category_rules: OptionMap<String, CommandRules>,
+
+    /// When the bot was removed from this server, in seconds since the Unix epoch.
+    /// The config is kept for a grace period in case the bot is added back.
+    pub(super) left_at: Option<u64>,
 }
 
 impl ServerConfig {
     pub const fn new() -> Self {
         Self {
-            prefix: None,
+            prefixes: None,
+            keep_default_prefix: None,
+            ignore_prefix_case: None,
             channels: ChannelSet {
                 log: None,
                 spam: None,
@@ -65,14 +164,229 @@ impl ServerConfig {
             auto_responses: None,
             auto_reacts: None,
             significant_reaction_count: None,
+            aliases: None,
+            suggest_commands: None,
+            command_rules: None,
+            category_rules: None,
+            left_at: None,
         }
     }
 
-    pub fn prefix(&self) -> String {
-        self.prefix.clone().unwrap_or_else(|| String::from("~"))
+    /// The prefixes commands can be used with, besides mentioning the bot, starting with the
+    /// bot's `default` prefix unless this server replaced it.
+    pub fn prefixes(&self, default: &str) -> Vec<String> {
+        let own = self.prefixes.as_deref().unwrap_or_default();
+        let mut prefixes = Vec::with_capacity(own.len() + 1);
+        if self.keep_default_prefix.unwrap_or(true) || own.is_empty() {
+            prefixes.push(String::from(default));
+        }
+        for pfx in own {
+            if !prefixes.contains(pfx) {
+                prefixes.push(pfx.clone());
+            }
+        }
+        prefixes
     }
 
+    /// The first prefix, used when showing commands.
+    pub fn prefix(&self, default: &str) -> String {
+        self.prefixes(default).swap_remove(0)
+    }
+
+    /// Makes `pfx` the only prefix, replacing the default one.
     pub fn set_prefix(&mut self, pfx: &str) {
-        self.prefix = Some(String::from(pfx));
+        self.prefixes = Some(Box::new([String::from(pfx)]));
+        self.keep_default_prefix = Some(false);
+    }
+
+    /// Returns `false` if `pfx` already is a prefix.
+    pub fn add_prefix(&mut self, pfx: &str, default: &str) -> bool {
+        if self.prefixes(default).iter().any(|p| p == pfx) {
+            return false;
+        }
+        let mut own = self.prefixes.take().map(Vec::from).unwrap_or_default();
+        if own.is_empty() {
+            // The default was in use for lack of any other prefix, so it stays in use.
+            self.keep_default_prefix = None;
+        }
+        own.push(String::from(pfx));
+        self.prefixes = Some(own.into());
+        true
+    }
+
+    /// Returns `false` if `pfx` is not a prefix.
+    pub fn remove_prefix(&mut self, pfx: &str, default: &str) -> bool {
+        if !self.prefixes(default).iter().any(|p| p == pfx) {
+            return false;
+        }
+        if pfx == default {
+            self.keep_default_prefix = Some(false);
+        }
+        let own = self.prefixes.take().map(Vec::from).unwrap_or_default();
+        let own = own.into_iter().filter(|p| p != pfx).collect::<Box<[_]>>();
+        self.prefixes = (!own.is_empty()).then_some(own);
+        true
+    }
+
+    pub fn reset_prefixes(&mut self) {
+        self.prefixes = None;
+        self.keep_default_prefix = None;
+    }
+
+    pub fn ignores_prefix_case(&self) -> bool {
+        self.ignore_prefix_case.unwrap_or(false)
+    }
+
+    /// Strips whichever prefix `word` starts with, preferring the longest so `~~` wins over `~`.
+    pub fn strip_prefix<'a>(&self, word: &'a str, default: &str) -> Option<&'a str> {
+        let mut prefixes = self.prefixes(default);
+        prefixes.sort_unstable_by_key(|p| std::cmp::Reverse(p.len()));
+        prefixes.iter().find_map(|pfx| {
+            if !self.ignores_prefix_case() {
+                return word.strip_prefix(pfx.as_str());
+            }
+            // Lowercasing can change how long a character is, e.g. `İ` becomes `i̇`, so the
+            // prefix may take up more or fewer bytes of `word` than it does on its own.
+            let pfx = pfx.to_lowercase();
+            word.char_indices()
+                .skip(1)
+                .map(|(end, _)| end)
+                .chain([word.len()])
+                .map(|end| (end, word[..end].to_lowercase()))
+                .take_while(|(_, head)| head.len() <= pfx.len())
+                .find(|(_, head)| *head == pfx)
+                .map(|(end, _)| &word[end..])
+        })
+    }
+
+    /// The expansion of the alias `name`, whose name is matched regardless of case if prefixes
+    /// are, for aliases added before names were stored lowercase.
+    pub fn alias(&self, name: &str) -> Option<&String> {
+        let aliases = self.aliases.as_ref()?;
+        aliases.get(name).or_else(|| {
+            aliases
+                .iter()
+                .find(|(n, _)| {
+                    self.ignores_prefix_case() && n.to_lowercase() == name.to_lowercase()
+                })
+                .map(|(_, expansion)| expansion)
+        })
+    }
+
+    pub fn suggests_commands(&self) -> bool {
+        self.suggest_commands.unwrap_or(true)
+    }
+
+    /// Why the top level `command` in `category` may not be used in `channel` by a member with
+    /// `roles`, checking the category's rules before the command's.
+    pub fn command_denial(
+        &self,
+        command: &str,
+        category: &str,
+        channel: Id<ChannelMarker>,
+        roles: &[Id<RoleMarker>],
+    ) -> Option<String> {
+        let category_rules = self.category_rules.as_ref().and_then(|r| r.get(category));
+        let command_rules = self.command_rules.as_ref().and_then(|r| r.get(command));
+        category_rules
+            .and_then(|r| r.denial(&format!("{category} commands"), channel, roles))
+            .or_else(|| command_rules?.denial(&format!("`{command}`"), channel, roles))
+    }
+
+    /// Marks this config as belonging to a server the bot was removed from.
+    pub fn archive(&mut self) {
+        self.left_at.get_or_insert_with(|| unix_time().as_secs());
+    }
+
+    /// Marks the bot as back in this server, returning whether the config had been archived.
+    pub fn restore(&mut self) -> bool {
+        self.left_at.take().is_some()
+    }
+
+    /// When the bot was removed from this server, for storage which keeps it in a column of its
+    /// own.
+    pub const fn left_at(&self) -> Option<u64> {
+        self.left_at
+    }
+
+    pub const fn set_left_at(&mut self, left_at: Option<u64>) {
+        self.left_at = left_at;
+    }
+
+    /// Whether the bot was removed from this server more than `grace` ago.
+    pub fn expired(&self, grace: Duration) -> bool {
+        self.left_at
+            .is_some_and(|left_at| unix_time().saturating_sub(Duration::from_secs(left_at)) > grace)
+    }
+}
+
+fn unix_time() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::ServerConfig;
+
+    #[test]
+    fn uses_the_default_prefix_until_another_is_set() {
+        let mut config = ServerConfig::new();
+        assert_eq!(config.prefixes("~"), ["~"]);
+        config.set_prefix("!");
+        assert_eq!(config.prefixes("~"), ["!"]);
+        config.reset_prefixes();
+        assert_eq!(config.prefixes("$"), ["$"]);
+    }
+
+    #[test]
+    fn added_prefixes_follow_a_changed_default() {
+        let mut config = ServerConfig::new();
+        assert!(config.add_prefix("!", "~"));
+        assert!(!config.add_prefix("~", "~"));
+        assert_eq!(config.prefixes("~"), ["~", "!"]);
+        assert_eq!(config.prefixes("$"), ["$", "!"]);
+        assert_eq!(config.prefixes.as_deref(), Some(&[String::from("!")][..]));
+    }
+
+    #[test]
+    fn strips_prefixes_regardless_of_case_when_asked() {
+        let mut config = ServerConfig::new();
+        config.set_prefix("İ");
+        assert_eq!(config.strip_prefix("İhelp", "~"), Some("help"));
+        assert_eq!(config.strip_prefix("i\u{307}help", "~"), None);
+
+        config.ignore_prefix_case = Some(true);
+        assert_eq!(config.strip_prefix("İhelp", "~"), Some("help"));
+        assert_eq!(config.strip_prefix("i\u{307}help", "~"), Some("help"));
+        assert_eq!(config.strip_prefix("ihelp", "~"), None);
+        assert_eq!(config.strip_prefix("é", "~"), None);
+    }
+
+    #[test]
+    fn finds_aliases_regardless_of_case_when_asked() {
+        let mut config = ServerConfig::new();
+        config.aliases = Some(HashMap::from([(
+            String::from("LoFi"),
+            String::from("play lofi"),
+        )]));
+        assert!(config.alias("lofi").is_none());
+        config.ignore_prefix_case = Some(true);
+        assert_eq!(config.alias("lofi").map(String::as_str), Some("play lofi"));
+    }
+
+    #[test]
+    fn removes_the_default_prefix_or_added_ones() {
+        let mut config = ServerConfig::new();
+        config.add_prefix("!", "~");
+        config.add_prefix("?", "~");
+        assert!(config.remove_prefix("~", "~"));
+        assert_eq!(config.prefixes("$"), ["!", "?"]);
+        assert!(config.remove_prefix("!", "~"));
+        assert!(!config.remove_prefix("!", "~"));
+        assert_eq!(config.prefixes("~"), ["?"]);
     }
 }
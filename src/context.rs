@@ -0,0 +1,434 @@
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use twilight_http::error::ErrorType;
+use twilight_model::{
+    application::interaction::{Interaction, InteractionData},
+    channel::{
+        message::{AllowedMentions, Embed},
+        Attachment, Message,
+    },
+    guild::Permissions,
+    http::{
+        attachment,
+        interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+    },
+    id::{
+        marker::{
+            ApplicationMarker, ChannelMarker, GuildMarker, InteractionMarker, MessageMarker,
+            RoleMarker,
+        },
+        Id,
+    },
+    user::User,
+};
+use twilight_util::permission_calculator::PermissionCalculator;
+
+use crate::{error::CommandError, State};
+
+/// What a command was invoked by.
+#[derive(Clone)]
+pub enum Source {
+    /// A prefixed text command, replies are sent as replies to this message.
+    Message(Id<MessageMarker>),
+    /// A slash command, replies are sent as interaction responses and followups.
+    Interaction {
+        application_id: Id<ApplicationMarker>,
+        id: Id<InteractionMarker>,
+        token: String,
+    },
+}
+
+/// How far along responding to the invocation is.
+/// Discord wants exactly one initial response to an interaction, anything after that has to be a
+/// followup, and only the first reply to a text command may replace an earlier one.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    Pending,
+    Deferred,
+    Responded,
+}
+
+/// Everything a command needs to know about where it was invoked, and how to answer,
+/// regardless of whether it came from a text message or a slash command.
+#[derive(Clone)]
+pub struct Context {
+    pub state: State,
+    pub guild_id: Option<Id<GuildMarker>>,
+    pub channel_id: Id<ChannelMarker>,
+    pub author: User,
+    /// The author's roles, empty outside of a guild.
+    pub roles: Vec<Id<RoleMarker>>,
+    /// Files attached to the invoking message, or given to a slash command's file option.
+    pub attachments: Vec<Attachment>,
+    pub source: Source,
+    /// The author's permissions as sent along with an interaction.
+    interaction_permissions: Option<Permissions>,
+    stage: Arc<Mutex<Stage>>,
+}
+
+/// A plain embed with a title and description, to be filled in further by the caller.
+pub fn embed(title: &str, description: &str) -> Embed {
+    Embed {
+        author: None,
+        color: None,
+        description: Some(String::from(description)),
+        fields: vec![],
+        footer: None,
+        image: None,
+        kind: String::from("rich"),
+        provider: None,
+        thumbnail: None,
+        timestamp: None,
+        title: Some(String::from(title)),
+        url: None,
+        video: None,
+    }
+}
+
+impl Context {
+    pub fn new(
+        state: State,
+        guild_id: Option<Id<GuildMarker>>,
+        channel_id: Id<ChannelMarker>,
+        author: User,
+        source: Source,
+    ) -> Self {
+        Self {
+            state,
+            guild_id,
+            channel_id,
+            author,
+            roles: vec![],
+            attachments: vec![],
+            source,
+            interaction_permissions: None,
+            stage: Arc::new(Mutex::new(Stage::Pending)),
+        }
+    }
+
+    pub fn from_message(state: State, msg: &Message) -> Self {
+        let mut ctx = Self::new(
+            state,
+            msg.guild_id,
+            msg.channel_id,
+            msg.author.clone(),
+            Source::Message(msg.id),
+        );
+        ctx.roles = msg
+            .member
+            .as_ref()
+            .map(|m| m.roles.clone())
+            .unwrap_or_default();
+        ctx.attachments = msg.attachments.clone();
+        ctx
+    }
+
+    /// Returns `None` for interactions without a channel or user, which Discord never sends for
+    /// application commands.
+    pub fn from_interaction(state: State, interaction: &Interaction) -> Option<Self> {
+        let mut ctx = Self::new(
+            state,
+            interaction.guild_id,
+            interaction.channel.as_ref()?.id,
+            interaction.author()?.clone(),
+            Source::Interaction {
+                application_id: interaction.application_id,
+                id: interaction.id,
+                token: interaction.token.clone(),
+            },
+        );
+        if let Some(member) = &interaction.member {
+            ctx.roles = member.roles.clone();
+            ctx.interaction_permissions = member.permissions;
+        }
+        if let Some(InteractionData::ApplicationCommand(data)) = &interaction.data {
+            ctx.attachments = data
+                .resolved
+                .iter()
+                .flat_map(|resolved| resolved.attachments.values().cloned())
+                .collect();
+        }
+        Some(ctx)
+    }
+
+    /// The guild the command was used in, failing with `CommandError::GuildOnly` in DMs.
+    pub fn guild(&self) -> Result<Id<GuildMarker>, CommandError> {
+        self.guild_id.ok_or(CommandError::GuildOnly)
+    }
+
+    /// The voice channel the author is connected to in this guild.
+    pub async fn voice_channel(&self) -> anyhow::Result<Id<ChannelMarker>> {
+        let guild_id = self.guild()?;
+        let voice_state = match self
+            .state
+            .http
+            .user_voice_state(guild_id, self.author.id)
+            .await
+        {
+            Ok(response) => response.model().await?,
+            // Discord answers with a 404 for members who are not in a voice channel.
+            Err(why)
+                if matches!(
+                    why.kind(),
+                    ErrorType::Response { status, .. } if status.get() == 404
+                ) =>
+            {
+                return Err(CommandError::NotInVoice.into());
+            }
+            Err(why) => return Err(why.into()),
+        };
+        Ok(voice_state.channel_id.ok_or(CommandError::NotInVoice)?)
+    }
+
+    /// The permissions the author has in the channel the command was used in, taking their roles
+    /// and the channel's overwrites into account. Outside of a guild nothing is off limits.
+    /// Anything missing from the cache is asked of Discord. Returns `None` when they cannot be
+    /// worked out even so.
+    pub async fn permissions(&self) -> Option<Permissions> {
+        let Some(guild) = self.guild_id else {
+            return Some(Permissions::all());
+        };
+        let permissions = match self.interaction_permissions {
+            Some(permissions) => permissions,
+            None => match self
+                .state
+                .cache
+                .permissions()
+                .in_channel(self.author.id, self.channel_id)
+            {
+                Ok(permissions) => permissions,
+                Err(_) => self
+                    .fetch_permissions(guild)
+                    .await
+                    .inspect_err(|why| tracing::warn!("could not work out permissions: {why:?}"))
+                    .ok()?,
+            },
+        };
+        if permissions.contains(Permissions::ADMINISTRATOR) {
+            return Some(Permissions::all());
+        }
+        Some(permissions)
+    }
+
+    /// Works out the author's permissions in this channel from what Discord sends about the
+    /// guild, the author and the channel, taking a thread's overwrites from its parent.
+    async fn fetch_permissions(&self, guild: Id<GuildMarker>) -> anyhow::Result<Permissions> {
+        let http = &self.state.http;
+        let member = http
+            .guild_member(guild, self.author.id)
+            .await?
+            .model()
+            .await?;
+        let guild_info = http.guild(guild).await?.model().await?;
+        let mut channel = http.channel(self.channel_id).await?.model().await?;
+        let kind = channel.kind;
+        if kind.is_thread() {
+            if let Some(parent) = channel.parent_id {
+                channel = http.channel(parent).await?.model().await?;
+            }
+        }
+
+        let role = |id: Id<RoleMarker>| {
+            guild_info
+                .roles
+                .iter()
+                .find(|r| r.id == id)
+                .map(|r| r.permissions)
+        };
+        let everyone = role(guild.cast()).unwrap_or_else(Permissions::empty);
+        let roles = member
+            .roles
+            .iter()
+            .filter_map(|&id| Some((id, role(id)?)))
+            .collect::<Vec<_>>();
+        let overwrites = channel.permission_overwrites.unwrap_or_default();
+        Ok(
+            PermissionCalculator::new(guild, self.author.id, everyone, &roles)
+                .owner_id(guild_info.owner_id)
+                .in_channel(kind, &overwrites),
+        )
+    }
+
+    /// Lets the user know the command is being worked on, for commands which may take longer than
+    /// the 3 seconds Discord allows before an interaction must be responded to.
+    pub async fn defer(&self) -> anyhow::Result<()> {
+        match &self.source {
+            Source::Message(_) => {
+                self.state
+                    .http
+                    .create_typing_trigger(self.channel_id)
+                    .await?;
+            }
+            Source::Interaction {
+                application_id,
+                id,
+                token,
+            } => {
+                let mut stage = self.stage.lock().await;
+                if *stage == Stage::Pending {
+                    self.state
+                        .http
+                        .interaction(*application_id)
+                        .create_response(
+                            *id,
+                            token,
+                            &InteractionResponse {
+                                kind: InteractionResponseType::DeferredChannelMessageWithSource,
+                                data: None,
+                            },
+                        )
+                        .await?;
+                    *stage = Stage::Deferred;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn send(
+        &self,
+        content: Option<&str>,
+        embeds: &[Embed],
+        files: &[attachment::Attachment],
+    ) -> anyhow::Result<()> {
+        let allowed_mentions = AllowedMentions::default();
+        match &self.source {
+            Source::Message(message_id) => {
+                let mut stage = self.stage.lock().await;
+                let responses = &self.state.responses;
+                // A command re-run by editing its message edits its earlier reply.
+                if *stage == Stage::Pending {
+                    if let Some(response) = responses.response(*message_id).await {
+                        let edited = self
+                            .state
+                            .http
+                            .update_message(self.channel_id, response)
+                            .allowed_mentions(Some(&allowed_mentions))
+                            .content(content)
+                            .embeds(Some(embeds))
+                            .attachments(files)
+                            .await;
+                        // The earlier reply may have been deleted, in which case reply anew.
+                        if edited.is_ok() {
+                            *stage = Stage::Responded;
+                            return Ok(());
+                        }
+                    }
+                }
+
+                let mut request = self
+                    .state
+                    .http
+                    .create_message(self.channel_id)
+                    .allowed_mentions(Some(&allowed_mentions))
+                    .embeds(embeds)
+                    .attachments(files)
+                    .reply(*message_id);
+                if let Some(content) = content {
+                    request = request.content(content);
+                }
+                let response = request.await?.model().await?;
+                if *stage == Stage::Pending {
+                    responses.responded(*message_id, response.id).await;
+                }
+                *stage = Stage::Responded;
+            }
+            Source::Interaction {
+                application_id,
+                id,
+                token,
+            } => {
+                let client = self.state.http.interaction(*application_id);
+                let mut stage = self.stage.lock().await;
+                match *stage {
+                    Stage::Pending => {
+                        client
+                            .create_response(
+                                *id,
+                                token,
+                                &InteractionResponse {
+                                    kind: InteractionResponseType::ChannelMessageWithSource,
+                                    data: Some(InteractionResponseData {
+                                        allowed_mentions: Some(allowed_mentions),
+                                        content: content.map(String::from),
+                                        embeds: Some(embeds.to_vec()),
+                                        attachments: Some(files.to_vec()),
+                                        ..Default::default()
+                                    }),
+                                },
+                            )
+                            .await?;
+                    }
+                    Stage::Deferred => {
+                        client
+                            .update_response(token)
+                            .allowed_mentions(Some(&allowed_mentions))
+                            .content(content)
+                            .embeds(Some(embeds))
+                            .attachments(files)
+                            .await?;
+                    }
+                    Stage::Responded => {
+                        let mut request = client
+                            .create_followup(token)
+                            .allowed_mentions(Some(&allowed_mentions))
+                            .embeds(embeds)
+                            .attachments(files);
+                        if let Some(content) = content {
+                            request = request.content(content);
+                        }
+                        request.await?;
+                    }
+                }
+                *stage = Stage::Responded;
+            }
+        }
+        Ok(())
+    }
+
+    /// Answers the invocation with a message.
+    pub async fn reply(&self, content: &str) -> anyhow::Result<()> {
+        self.send(Some(content), &[], &[]).await
+    }
+
+    /// Answers the invocation with an embed.
+    pub async fn reply_embed(&self, embed: Embed) -> anyhow::Result<()> {
+        self.send(None, &[embed], &[]).await
+    }
+
+    /// Answers the invocation with a message and a file.
+    pub async fn reply_file(
+        &self,
+        content: &str,
+        file: attachment::Attachment,
+    ) -> anyhow::Result<()> {
+        self.send(Some(content), &[], &[file]).await
+    }
+
+    /// Sends another message after the command has already replied.
+    /// For text commands this is a plain message in the channel rather than another reply.
+    pub async fn followup(&self, content: &str) -> anyhow::Result<()> {
+        if let Source::Message(_) = self.source {
+            self.state
+                .http
+                .create_message(self.channel_id)
+                .allowed_mentions(Some(&AllowedMentions::default()))
+                .content(content)
+                .await?;
+            return Ok(());
+        }
+        self.send(Some(content), &[], &[]).await
+    }
+
+    /// Makes sure an interaction is answered once its command is done, even if the command
+    /// had nothing to say, so Discord does not show the interaction as failed.
+    pub async fn finish(&self) -> anyhow::Result<()> {
+        if matches!(self.source, Source::Interaction { .. })
+            && *self.stage.lock().await != Stage::Responded
+        {
+            self.reply("Done.").await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use tokio::sync::Mutex;
+
+use crate::{
+    context::Context,
+    parser::{Bucket, Cooldown},
+};
+
+/// Once this many cooldowns are tracked, expired ones are cleared out on the next use.
+const PRUNE_AFTER: usize = 1024;
+
+/// The outcome of using a command which has a cooldown.
+pub enum Check {
+    /// The command can run, and its cooldown has started.
+    Ready,
+    /// The command is cooling down for this long, and the user has not been told yet.
+    Cooling(Duration),
+    /// The command is cooling down and the user has already been told, so stay quiet.
+    Silenced,
+}
+
+struct Entry {
+    until: Instant,
+    warned: bool,
+}
+
+/// When each command can next be used, by each user, channel or guild.
+#[derive(Default)]
+pub struct Cooldowns {
+    entries: Mutex<HashMap<(String, Bucket, u64), Entry>>,
+}
+
+impl Cooldowns {
+    /// Records a use of the command at `path`, e.g. `alias add`, by whoever `ctx` belongs to.
+    /// Commands used in DMs share their cooldown with the DM channel rather than a guild.
+    pub async fn check(&self, path: &str, cooldown: Cooldown, ctx: &Context) -> Check {
+        let id = match cooldown.bucket {
+            Bucket::User => ctx.author.id.get(),
+            Bucket::Channel => ctx.channel_id.get(),
+            Bucket::Guild => ctx.guild_id.map_or(ctx.channel_id.get(), |g| g.get()),
+        };
+        let key = (String::from(path), cooldown.bucket, id);
+        let now = Instant::now();
+
+        let mut entries = self.entries.lock().await;
+        if entries.len() >= PRUNE_AFTER {
+            entries.retain(|_, entry| entry.until > now);
+        }
+
+        match entries.get_mut(&key) {
+            Some(entry) if entry.until > now => {
+                if entry.warned {
+                    Check::Silenced
+                } else {
+                    entry.warned = true;
+                    Check::Cooling(entry.until - now)
+                }
+            }
+            _ => {
+                entries.insert(
+                    key,
+                    Entry {
+                        until: now + cooldown.duration,
+                        warned: false,
+                    },
+                );
+                Check::Ready
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    fmt,
+    future::Future,
+    random::{DefaultRandomSource, Random},
+};
+
+use crate::context::Context;
+
+/// A command failing for a reason the user can do something about.
+/// Commands return these through `anyhow` and the message is shown to the user as is, anything
+/// else is treated as a bug.
+#[derive(Debug)]
+pub enum CommandError {
+    /// The command only makes sense in a server.
+    GuildOnly,
+    /// The author has to be in a voice channel.
+    NotInVoice,
+    /// Nothing is queued in this server.
+    NothingQueued,
+    /// A search came back empty.
+    NoResults(String),
+    /// A channel which is not in this server.
+    UnknownChannel,
+    /// A role which is not in this server, or `@everyone`.
+    UnknownRole,
+    /// A role at or above the bot's highest role, which it cannot give out.
+    RoleTooHigh,
+    /// The bot's own roles could not be looked up, so it can't tell which roles it can give out.
+    OwnRolesUnknown,
+    /// An emoji which is not one of this server's custom emoji.
+    UnknownEmoji,
+    /// A message id which does not point at a message in the given channel.
+    UnknownMessage,
+    /// A settings file which could not be imported, and why.
+    InvalidImport(String),
+    /// A prefix which is empty or contains whitespace, so it could never be typed.
+    InvalidPrefix,
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::GuildOnly => write!(f, "This command can only be used in a server."),
+            Self::NotInVoice => write!(f, "You need to be in a voice channel for that."),
+            Self::NothingQueued => write!(f, "Nothing is queued, add a song with `play` first."),
+            Self::NoResults(query) => write!(f, "Nothing was found for `{query}`."),
+            Self::UnknownChannel => write!(f, "That channel is not in this server."),
+            Self::UnknownRole => write!(f, "That role is not one of this server's roles."),
+            Self::RoleTooHigh => write!(
+                f,
+                "That role is not below my highest role, so I can't give it to anyone."
+            ),
+            Self::OwnRolesUnknown => write!(
+                f,
+                "I couldn't look up my own roles in this server, try again in a moment."
+            ),
+            Self::UnknownEmoji => write!(f, "That is not one of this server's custom emoji."),
+            Self::UnknownMessage => write!(f, "There is no such message in that channel."),
+            Self::InvalidImport(why) => write!(f, "That file can't be imported, {why}"),
+            Self::InvalidPrefix => write!(f, "A prefix can't be empty or contain spaces."),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+/// Runs `command` to completion and tells the user why it failed, if it did.
+/// It runs as a task of its own so a panic is reported like any other internal error.
+pub async fn run(
+    ctx: &Context,
+    name: &str,
+    command: impl Future<Output = anyhow::Result<()>> + Send + 'static,
+) {
+    let result = tokio::spawn(command)
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result);
+    report(ctx, name, result).await;
+}
+
+/// Tells the user why `command` failed.
+/// Errors which are not a `CommandError` are logged along with a short id, which the user is
+/// given too so the two can be matched up.
+async fn report(ctx: &Context, command: &str, result: anyhow::Result<()>) {
+    let Err(why) = result else {
+        return;
+    };
+
+    let content = if let Some(error) = why.downcast_ref::<CommandError>() {
+        tracing::debug!("`{command}` failed: {error}");
+        error.to_string()
+    } else {
+        let id = format!("{:08x}", u32::random(&mut DefaultRandomSource));
+        tracing::error!(error_id = %id, "`{command}` failed: {why:?}");
+        format!("Something went wrong running `{command}`. If it keeps happening, mention error `{id}`.")
+    };
+
+    if let Err(why) = ctx.reply(&content).await {
+        tracing::warn!("could not report the failure of `{command}`: {why}");
+    }
+}
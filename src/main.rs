@@ -1,37 +1,49 @@
-#![feature(pattern)]
 #![feature(random)]
-#![feature(duration_constants)]
 
 use std::{collections::HashMap, env, sync::Arc};
 
+use config::global::BotConfig;
+use cooldowns::Cooldowns;
 use reqwest::Client;
+use responses::Responses;
 use songbird::{shards::TwilightMap, Songbird};
 use state::{Handler, State, StateRef};
+use storage::Backend;
 use tokio::sync::Mutex;
 use twilight_cache_inmemory::DefaultInMemoryCache;
-use twilight_gateway::{EventTypeFlags, Intents, Shard, ShardId, StreamExt};
+use twilight_gateway::{EventTypeFlags, Shard, ShardId, StreamExt};
 use twilight_http::Client as HttpClient;
+use twilight_standby::Standby;
 
 mod commands;
 mod config;
+mod context;
+mod cooldowns;
+mod error;
 mod music;
 mod parser;
+mod responses;
 mod state;
+mod storage;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    tracing_subscriber::fmt::init();
+    let bot = BotConfig::load()?;
+    tracing_subscriber::fmt()
+        .with_max_level(bot.log_level)
+        .init();
 
     let token = &env::var("TOKEN")?;
+    let storage = Backend::open(&bot.storage, &bot.data_dir)?;
 
     let cache = DefaultInMemoryCache::builder()
-        .message_cache_size(10)
+        .message_cache_size(bot.message_cache_size)
         .build();
 
     let http = HttpClient::new(String::from(token));
     let user = http.current_user().await?.model().await?;
 
-    let shard = Shard::new(ShardId::ONE, String::from(token), Intents::all());
+    let shard = Shard::new(ShardId::ONE, String::from(token), bot.intents);
 
     let shards: Vec<Shard> = vec![shard];
 
@@ -50,10 +62,18 @@ async fn main() -> anyhow::Result<()> {
         songbird,
         Mutex::new(HashMap::new()),
         Mutex::new(HashMap::new()),
+        Mutex::new(HashMap::new()),
         Client::new(),
         cache,
+        storage,
+        Cooldowns::default(),
+        Responses::default(),
+        Standby::new(),
+        bot,
     ));
+    s.read_configs_from_file().await?;
     Arc::clone(&s).generate_configs().await?;
+    s.register_commands().await?;
     tracing::info!("Logged in as: {}", user.name);
 
     let mut set = tokio::task::JoinSet::new();
@@ -61,6 +81,7 @@ async fn main() -> anyhow::Result<()> {
         set.spawn(tokio::spawn(runner(shard, Arc::clone(&s))));
         set.spawn(tokio::spawn(Arc::clone(&s).check_done_vcs()));
         set.spawn(tokio::spawn(Arc::clone(&s).leave_empty_vcs()));
+        set.spawn(tokio::spawn(Arc::clone(&s).purge_departed_guilds()));
     }
 
     set.join_next().await;
@@ -84,7 +105,7 @@ async fn runner(mut shard: Shard, s: State) -> anyhow::Result<()> {
                     match s.handle_event(event).await {
                         Ok(()) => {}
                         Err(why) => {
-                            tracing::debug!("Error processing event: {why}.");
+                            tracing::error!("Error processing event: {why:?}");
                         }
                     }
                 }
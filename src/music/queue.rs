@@ -1,5 +1,6 @@
 use std::time::Duration;
 
+use reqwest::Client;
 use songbird::{
     input::{AuxMetadata, Compose, YoutubeDl},
     tracks::TrackHandle,
@@ -10,7 +11,7 @@ use twilight_model::id::{
     Id,
 };
 
-use crate::state::State;
+use crate::{error::CommandError, state::State};
 
 #[derive(Clone)]
 pub struct Queue<'a> {
@@ -151,14 +152,26 @@ impl Queue<'static> {
         }
     }
 
-    pub async fn push(&mut self, state: State, url: String) -> anyhow::Result<AuxMetadata> {
-        let mut search = YoutubeDl::new_search(state.client.clone(), url.clone());
-        let res = search.search(Some(1)).await?.collect::<Vec<_>>();
-        let metadata = &res[0];
-        let url = metadata.source_url.clone().unwrap();
+    /// The first result for `query`, which may also be a link, failing with
+    /// `CommandError::NoResults` when there is none.
+    pub async fn search(
+        client: Client,
+        query: String,
+    ) -> anyhow::Result<(YoutubeDl<'static>, AuxMetadata)> {
+        let mut search = YoutubeDl::new_search(client.clone(), query.clone());
+        let Some(metadata) = search.search(Some(1)).await?.next() else {
+            return Err(CommandError::NoResults(query).into());
+        };
+        let Some(url) = metadata.source_url.clone() else {
+            return Err(CommandError::NoResults(query).into());
+        };
+        Ok((YoutubeDl::new(client, url), metadata))
+    }
 
-        self.songs.push(YoutubeDl::new(state.client.clone(), url));
-        Ok(metadata.clone())
+    pub async fn push(&mut self, state: State, query: String) -> anyhow::Result<AuxMetadata> {
+        let (song, metadata) = Queue::search(state.client.clone(), query).await?;
+        self.songs.push(song);
+        Ok(metadata)
     }
 
     pub async fn goto(
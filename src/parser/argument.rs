@@ -1,17 +1,46 @@
+use std::{
+    iter::{self, Peekable},
+    time::Duration,
+    vec,
+};
+
+use reqwest::Url;
+use twilight_model::{
+    channel::message::EmojiReactionType,
+    id::{
+        marker::{ChannelMarker, RoleMarker, UserMarker},
+        Id,
+    },
+};
+
+use super::parse;
+
 #[derive(Clone)]
 pub enum ArgumentWithData {
     String(String),
     UInt(u32),
     Int(i32),
     Bool(bool),
+    Duration(Duration),
+    User(Id<UserMarker>),
+    Channel(Id<ChannelMarker>),
+    Role(Id<RoleMarker>),
+    Emoji(EmojiReactionType),
+    Url(Url),
 }
 impl ArgumentWithData {
     pub fn new(arg: &Argument, val: String) -> anyhow::Result<Self> {
         Ok(match arg {
-            Argument::String(_) => Self::String(val),
+            Argument::String(_) | Argument::Rest(_) => Self::String(val),
             Argument::UInt(_) => Self::UInt(val.parse()?),
             Argument::Int(_) => Self::Int(val.parse()?),
             Argument::Bool(_) => Self::Bool(val.parse()?),
+            Argument::Duration(_) => Self::Duration(parse::duration(&val)?),
+            Argument::User(_) => Self::User(parse::user(&val)?),
+            Argument::Channel(_) => Self::Channel(parse::channel(&val)?),
+            Argument::Role(_) => Self::Role(parse::role(&val)?),
+            Argument::Emoji(_) => Self::Emoji(parse::emoji(&val)?),
+            Argument::Url(_) => Self::Url(parse::url(&val)?),
         })
     }
 
@@ -39,35 +68,221 @@ impl ArgumentWithData {
             _ => None,
         }
     }
+    pub fn duration(&self) -> Option<Duration> {
+        match self {
+            Self::Duration(d) => Some(*d),
+            _ => None,
+        }
+    }
+    pub fn user(&self) -> Option<Id<UserMarker>> {
+        match self {
+            Self::User(d) => Some(*d),
+            _ => None,
+        }
+    }
+    pub fn channel(&self) -> Option<Id<ChannelMarker>> {
+        match self {
+            Self::Channel(d) => Some(*d),
+            _ => None,
+        }
+    }
+    pub fn role(&self) -> Option<Id<RoleMarker>> {
+        match self {
+            Self::Role(d) => Some(*d),
+            _ => None,
+        }
+    }
+    pub fn emoji(&self) -> Option<EmojiReactionType> {
+        match self {
+            Self::Emoji(d) => Some(d.clone()),
+            _ => None,
+        }
+    }
+    pub fn url(&self) -> Option<Url> {
+        match self {
+            Self::Url(d) => Some(d.clone()),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct ArgumentMetadata {
     pub label: String,
     pub size: u32,
+    /// Whether the command refuses to run without this argument.
+    pub required: bool,
 }
 
 #[derive(Clone)]
 pub enum Argument {
     String(ArgumentMetadata),
+    /// The rest of the message, verbatim. Its `size` is ignored.
+    Rest(ArgumentMetadata),
     UInt(ArgumentMetadata),
     Int(ArgumentMetadata),
     Bool(ArgumentMetadata),
+    /// A human duration like `1h30m`.
+    Duration(ArgumentMetadata),
+    /// A user mention or id.
+    User(ArgumentMetadata),
+    /// A channel mention or id.
+    Channel(ArgumentMetadata),
+    /// A role mention or id.
+    Role(ArgumentMetadata),
+    /// A custom or unicode emoji.
+    Emoji(ArgumentMetadata),
+    /// An http(s) link.
+    Url(ArgumentMetadata),
 }
 
 impl Argument {
-    pub fn label(self: Argument) -> String {
+    const fn metadata(&self) -> &ArgumentMetadata {
         match self {
-            Argument::String(d) | Argument::UInt(d) | Argument::Int(d) | Argument::Bool(d) => {
-                d.label
-            }
+            Argument::String(d)
+            | Argument::Rest(d)
+            | Argument::UInt(d)
+            | Argument::Int(d)
+            | Argument::Bool(d)
+            | Argument::Duration(d)
+            | Argument::User(d)
+            | Argument::Channel(d)
+            | Argument::Role(d)
+            | Argument::Emoji(d)
+            | Argument::Url(d) => d,
         }
     }
+
+    pub fn label(self: Argument) -> String {
+        self.metadata().label.clone()
+    }
     pub fn size(self: Argument) -> u32 {
+        self.metadata().size
+    }
+    pub const fn required(&self) -> bool {
+        self.metadata().required
+    }
+
+    /// A short description of what this argument accepts, for error messages.
+    pub const fn kind(&self) -> &'static str {
         match self {
-            Argument::String(d) | Argument::UInt(d) | Argument::Int(d) | Argument::Bool(d) => {
-                d.size
+            Argument::String(_) => "a word",
+            Argument::Rest(_) => "some text",
+            Argument::UInt(_) => "a positive number",
+            Argument::Int(_) => "a number",
+            Argument::Bool(_) => "`true` or `false`",
+            Argument::Duration(_) => "a duration like `1h30m`",
+            Argument::User(_) => "a user mention or id",
+            Argument::Channel(_) => "a channel mention or id",
+            Argument::Role(_) => "a role mention or id",
+            Argument::Emoji(_) => "an emoji",
+            Argument::Url(_) => "a link",
+        }
+    }
+
+    /// How this argument is written in a usage line, `<label>` when required and `[label]` when not.
+    pub fn usage(&self) -> String {
+        let meta = self.metadata();
+        let repeats = if matches!(self, Argument::Rest(_)) || meta.size == 0 {
+            "..."
+        } else {
+            ""
+        };
+        if meta.required {
+            format!("<{}{repeats}>", meta.label)
+        } else {
+            format!("[{}{repeats}]", meta.label)
+        }
+    }
+}
+
+/// The rest of the message as a single string, see `Argument::Rest`.
+pub struct Rest(pub String);
+
+/// A type a single parsed argument can be turned back into.
+pub trait ArgumentType: Sized {
+    fn argument(meta: ArgumentMetadata) -> Argument;
+    fn from_data(data: &ArgumentWithData) -> Option<Self>;
+}
+
+macro_rules! argument_type {
+    ($ty:ty, $variant:ident, $accessor:ident) => {
+        impl ArgumentType for $ty {
+            fn argument(meta: ArgumentMetadata) -> Argument {
+                Argument::$variant(meta)
+            }
+            fn from_data(data: &ArgumentWithData) -> Option<Self> {
+                data.$accessor()
             }
         }
+    };
+}
+
+argument_type!(String, String, string);
+argument_type!(u32, UInt, uint);
+argument_type!(i32, Int, int);
+argument_type!(bool, Bool, bool);
+argument_type!(Duration, Duration, duration);
+argument_type!(Id<UserMarker>, User, user);
+argument_type!(Id<ChannelMarker>, Channel, channel);
+argument_type!(Id<RoleMarker>, Role, role);
+argument_type!(EmojiReactionType, Emoji, emoji);
+argument_type!(Url, Url, url);
+
+impl ArgumentType for Rest {
+    fn argument(meta: ArgumentMetadata) -> Argument {
+        Argument::Rest(ArgumentMetadata { size: 0, ..meta })
+    }
+    fn from_data(data: &ArgumentWithData) -> Option<Self> {
+        data.string().map(Rest)
+    }
+}
+
+pub type Arguments = Peekable<vec::IntoIter<ArgumentWithData>>;
+
+/// The type of a parameter of a function defined with `command!`.
+/// `T` is a required argument, `Option<T>` an optional one and `Vec<T>` takes every remaining word.
+pub trait Parameter: Sized {
+    /// The argument this parameter is parsed as, labelled after the parameter's name.
+    fn argument(name: &str) -> Argument;
+    /// Takes this parameter's value off the front of the parsed arguments,
+    /// `None` only when a required argument is missing.
+    fn take(arguments: &mut Arguments) -> Option<Self>;
+}
+
+fn metadata(name: &str, size: u32, required: bool) -> ArgumentMetadata {
+    ArgumentMetadata {
+        label: name.replace('_', " "),
+        size,
+        required,
+    }
+}
+
+impl<T: ArgumentType> Parameter for T {
+    fn argument(name: &str) -> Argument {
+        <T as ArgumentType>::argument(metadata(name, 1, true))
+    }
+    fn take(arguments: &mut Arguments) -> Option<Self> {
+        let value = <T as ArgumentType>::from_data(arguments.peek()?)?;
+        arguments.next();
+        Some(value)
+    }
+}
+
+impl<T: ArgumentType> Parameter for Option<T> {
+    fn argument(name: &str) -> Argument {
+        <T as ArgumentType>::argument(metadata(name, 1, false))
+    }
+    fn take(arguments: &mut Arguments) -> Option<Self> {
+        Some(<T as Parameter>::take(arguments))
+    }
+}
+
+impl<T: ArgumentType> Parameter for Vec<T> {
+    fn argument(name: &str) -> Argument {
+        <T as ArgumentType>::argument(metadata(name, 0, true))
+    }
+    fn take(arguments: &mut Arguments) -> Option<Self> {
+        Some(iter::from_fn(|| <T as Parameter>::take(arguments)).collect())
     }
 }
@@ -1,85 +1,300 @@
-use std::{future::Future, pin::Pin};
+use std::{future::Future, pin::Pin, time::Duration};
 
-use twilight_model::gateway::payload::incoming::MessageCreate;
+use twilight_model::guild::Permissions;
 
-use crate::State;
+use crate::{context::Context, State};
 
-use super::{Argument, ArgumentWithData, TextCommand};
+use super::{suggest, Argument, ArgumentWithData, ParseError, TextCommand};
 
 type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
 
+pub(super) fn parse_argument(
+    argument: &Argument,
+    val: String,
+) -> Result<ArgumentWithData, ParseError> {
+    ArgumentWithData::new(argument, val.clone()).map_err(|why| ParseError::Invalid {
+        label: argument.clone().label(),
+        kind: argument.kind(),
+        value: val,
+        reason: why.to_string(),
+    })
+}
+
+fn parse_arguments(
+    t: &mut TextCommand,
+    arguments: &[Argument],
+) -> Result<Box<[ArgumentWithData]>, ParseError> {
+    let mut collected_args: Vec<ArgumentWithData> = vec![];
+    for argument in arguments {
+        let words = if let Argument::Rest(_) = argument {
+            t.rest().into_iter().collect()
+        } else if argument.clone().size() == 0 {
+            t.by_ref().collect()
+        } else {
+            t.next().into_iter().collect::<Vec<_>>()
+        };
+
+        if words.is_empty() {
+            if argument.required() {
+                return Err(ParseError::Missing {
+                    label: argument.clone().label(),
+                    kind: argument.kind(),
+                });
+            }
+            continue;
+        }
+
+        for word in words {
+            collected_args.push(parse_argument(argument, word)?);
+        }
+    }
+    Ok(collected_args.into())
+}
+
 #[derive(Clone)]
 pub struct CommandWithData {
-    pub name: String,
-    pub subcommand: Option<Box<CommandWithData>>,
     pub arguments: Option<Box<[ArgumentWithData]>>,
 }
 
 impl CommandWithData {
+    /// Parses the remaining words of `t` as `c`'s arguments.
+    /// `t` should already be past the words naming `c`, see `Command::resolve`.
     pub fn new(mut t: TextCommand, c: Command) -> anyhow::Result<CommandWithData> {
         Ok(CommandWithData {
-            name: c.name,
-            subcommand: c.subcommands.and_then(|subcommands| {
-                let mut res = None;
-                for subcommand in subcommands {
-                    if let Some(word) = t.next() {
-                        if subcommand.name == word {
-                            res = Some(Box::new(CommandWithData::new(t.clone(), subcommand).ok()?));
-                            break;
-                        }
-                    }
-                }
-                res
-            }),
-            arguments: c.arguments.and_then(|arguments| {
-                let mut collected_args: Vec<ArgumentWithData> = vec![];
-                for argument in arguments {
-                    if argument.clone().size() == 0 {
-                        for arg in t.clone().into_iter() {
-                            collected_args.push(ArgumentWithData::new(&argument, arg).ok()?);
-                        }
-                    } else {
-                        if let Some(arg) = t.next() {
-                            collected_args.push(ArgumentWithData::new(&argument, arg).ok()?);
-                        }
-                    }
-                }
-                Some(collected_args.into())
-            }),
+            arguments: c
+                .arguments
+                .map(|arguments| parse_arguments(&mut t, &arguments))
+                .transpose()?,
         })
     }
 }
 
+/// The section of the help listing a command shows up in.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Utility,
+    Fun,
+    Music,
+    Config,
+}
+
+impl Category {
+    pub const ALL: [Self; 4] = [Self::Utility, Self::Fun, Self::Music, Self::Config];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Utility => "Utility",
+            Self::Fun => "Fun",
+            Self::Music => "Music",
+            Self::Config => "Config",
+        }
+    }
+}
+
+/// Who shares a cooldown.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Bucket {
+    User,
+    Channel,
+    Guild,
+}
+
+/// How often a command can be used by each bucket.
+#[derive(Clone, Copy)]
+pub struct Cooldown {
+    pub bucket: Bucket,
+    pub duration: Duration,
+}
+
+/// Permission names the way Discord shows them, e.g. `Manage Server, Kick Members`.
+pub fn permission_names(permissions: Permissions) -> String {
+    permissions
+        .iter_names()
+        .map(|(name, _)| match name {
+            "MANAGE_GUILD" => String::from("Manage Server"),
+            _ => name
+                .split('_')
+                .map(|word| word[..1].to_owned() + &word[1..].to_lowercase())
+                .collect::<Vec<_>>()
+                .join(" "),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 #[derive(Clone)]
 pub struct Command {
     pub name: String,
-    pub function:
-        Option<fn(State, MessageCreate, CommandWithData) -> BoxFuture<anyhow::Result<()>>>,
+    pub aliases: Box<[String]>,
+    pub description: String,
+    pub category: Category,
+    /// What a member needs to be allowed to do to use this command.
+    pub permissions: Permissions,
+    pub cooldown: Option<Cooldown>,
+    /// Whether this command refuses to run outside of a server, e.g. in DMs.
+    pub guild_only: bool,
+    /// Whether this command reads a file attached to it, which slash commands are given as an
+    /// option.
+    pub takes_file: bool,
+    pub function: Option<fn(State, Context, CommandWithData) -> BoxFuture<anyhow::Result<()>>>,
     pub subcommands: Option<Box<[Command]>>,
+    /// The subcommand a group runs when the word after it is not one of its subcommands,
+    /// so `prefix !` runs `prefix set !`.
+    pub default_subcommand: Option<String>,
     pub arguments: Option<Box<[Argument]>>,
 }
 
 impl Command {
     pub fn new(
         name: String,
-        function: Option<
-            fn(State, MessageCreate, CommandWithData) -> BoxFuture<anyhow::Result<()>>,
-        >,
+        function: Option<fn(State, Context, CommandWithData) -> BoxFuture<anyhow::Result<()>>>,
         subcommands: &[Self],
         arguments: &[Argument],
     ) -> Self {
         Self {
             name,
+            aliases: Box::new([]),
+            description: String::new(),
+            category: Category::Utility,
+            permissions: Permissions::empty(),
+            cooldown: None,
+            guild_only: false,
+            takes_file: false,
             function,
             subcommands: Some(subcommands.into()),
+            default_subcommand: None,
             arguments: Some(arguments.into()),
         }
     }
 
+    pub fn aliases(mut self, aliases: &[&str]) -> Self {
+        self.aliases = aliases.iter().map(|a| String::from(*a)).collect();
+        self
+    }
+
+    /// This command's name followed by its aliases.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.name.as_str()).chain(self.aliases.iter().map(String::as_str))
+    }
+
+    /// Whether `name` is this command's name or one of its aliases.
+    pub fn is_called(&self, name: &str) -> bool {
+        self.names().any(|n| n == name)
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = String::from(description);
+        self
+    }
+
+    pub const fn category(mut self, category: Category) -> Self {
+        self.category = category;
+        self
+    }
+
+    /// Requires `permissions` for this command and, for a group, every one of its subcommands.
+    pub fn permissions(mut self, permissions: Permissions) -> Self {
+        self.require(permissions);
+        self
+    }
+
+    fn require(&mut self, permissions: Permissions) {
+        self.permissions |= permissions;
+        for sc in self.subcommands.iter_mut().flat_map(|sc| sc.iter_mut()) {
+            sc.require(permissions);
+        }
+    }
+
+    /// Makes this command and, for a group, every one of its subcommands only usable in servers.
+    pub fn guild_only(mut self, guild_only: bool) -> Self {
+        self.restrict(guild_only);
+        self
+    }
+
+    fn restrict(&mut self, guild_only: bool) {
+        self.guild_only |= guild_only;
+        for sc in self.subcommands.iter_mut().flat_map(|sc| sc.iter_mut()) {
+            sc.restrict(guild_only);
+        }
+    }
+
+    pub const fn takes_file(mut self, takes_file: bool) -> Self {
+        self.takes_file = takes_file;
+        self
+    }
+
+    /// Lets each `bucket` use this command only once every `duration`.
+    pub const fn cooldown(mut self, bucket: Bucket, duration: Duration) -> Self {
+        self.cooldown = Some(Cooldown { bucket, duration });
+        self
+    }
+
+    /// Runs the subcommand `name` when this group is used with words which are not a subcommand.
+    pub fn default_subcommand(mut self, name: &str) -> Self {
+        self.default_subcommand = Some(String::from(name));
+        self
+    }
+
+    /// The usage line for this command, e.g. `~insert <index> <song...>`.
+    pub fn usage(&self, prefix: &str) -> String {
+        let mut usage = format!("{prefix}{}", self.name);
+        for argument in self.arguments.iter().flatten() {
+            usage.push(' ');
+            usage += &argument.usage();
+        }
+        usage
+    }
+
+    /// Finds the top level command `name`, then follows the words in `t` down through its subcommands,
+    /// consuming them, regardless of case when `ignore_case` is set. Returns the deepest command
+    /// reached and the names of its parents, e.g. `"config log "`.
+    pub fn resolve(
+        &self,
+        name: &str,
+        t: &mut TextCommand,
+        ignore_case: bool,
+    ) -> Option<(Box<Self>, String)> {
+        let fold = |word: &str| {
+            if ignore_case {
+                word.to_lowercase()
+            } else {
+                String::from(word)
+            }
+        };
+        let mut cmd = self.find_command(&fold(name))?;
+        let mut parents = String::new();
+        while let Some(subcommand) = t.peek().and_then(|word| cmd.find_command(&fold(word))) {
+            _ = t.next();
+            parents += &format!("{} ", cmd.name);
+            cmd = subcommand;
+        }
+        // The words left are arguments for the group's default subcommand, if it has one, unless
+        // they start with what looks like a mistyped subcommand, which the caller points out.
+        if t.peek()
+            .is_some_and(|word| cmd.close_subcommands(word).is_empty())
+        {
+            if let Some(default) = cmd
+                .default_subcommand
+                .as_deref()
+                .and_then(|name| cmd.find_command(name))
+            {
+                parents += &format!("{} ", cmd.name);
+                cmd = default;
+            }
+        }
+        Some((cmd, parents))
+    }
+
+    /// The names of this group's subcommands `word` might be a typo of, ignoring case.
+    pub fn close_subcommands(&self, word: &str) -> Vec<&str> {
+        let names = self.subcommands.iter().flatten().flat_map(Self::names);
+        suggest(&word.to_lowercase(), names)
+    }
+
     pub fn find_command(&self, command: &str) -> Option<Box<Self>> {
         if let Some(sc) = &self.subcommands {
             for c in sc {
-                if c.name == command {
+                if c.is_called(command) {
                     return Some(Box::new((*c).clone()));
                 }
             }
@@ -87,3 +302,75 @@ impl Command {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Command, TextCommand};
+
+    fn tree() -> Command {
+        let leaf = |name: &str| Command::new(String::from(name), None, &[], &[]);
+        let prefix = Command::new(
+            String::from("prefix"),
+            None,
+            &[leaf("list"), leaf("set")],
+            &[],
+        )
+        .default_subcommand("set");
+        Command::new(String::new(), None, &[prefix], &[])
+    }
+
+    /// The full name of the command `message` resolves to, and the first word left over.
+    fn resolve(message: &str) -> (String, Option<String>) {
+        resolve_with_case(message, false)
+    }
+
+    fn resolve_with_case(message: &str, ignore_case: bool) -> (String, Option<String>) {
+        let mut t = TextCommand::new(message);
+        let name = t.next().unwrap();
+        let (cmd, parents) = tree().resolve(&name, &mut t, ignore_case).unwrap();
+        (format!("{parents}{}", cmd.name), t.next())
+    }
+
+    #[test]
+    fn resolves_named_subcommands() {
+        assert_eq!(resolve("prefix list"), (String::from("prefix list"), None));
+        assert_eq!(
+            resolve("prefix set !"),
+            (String::from("prefix set"), Some(String::from("!")))
+        );
+    }
+
+    #[test]
+    fn falls_back_to_the_default_subcommand() {
+        assert_eq!(
+            resolve("prefix !"),
+            (String::from("prefix set"), Some(String::from("!")))
+        );
+    }
+
+    #[test]
+    fn matches_subcommands_regardless_of_case_when_asked() {
+        assert_eq!(
+            resolve_with_case("Prefix LIST", true),
+            (String::from("prefix list"), None)
+        );
+        // Not taken as the prefix `LIST` either way.
+        assert_eq!(
+            resolve("prefix LIST"),
+            (String::from("prefix"), Some(String::from("LIST")))
+        );
+    }
+
+    #[test]
+    fn does_not_fall_back_for_a_mistyped_subcommand() {
+        assert_eq!(
+            resolve("prefix lsit"),
+            (String::from("prefix"), Some(String::from("lsit")))
+        );
+    }
+
+    #[test]
+    fn stops_at_a_group_used_alone() {
+        assert_eq!(resolve("prefix"), (String::from("prefix"), None));
+    }
+}
@@ -0,0 +1,34 @@
+use std::fmt;
+
+/// `ParseError` describes why the words given to a command could not be turned into its arguments.
+#[derive(Debug, Clone)]
+pub enum ParseError {
+    /// A required argument was not given.
+    Missing { label: String, kind: &'static str },
+    /// An argument was given, but could not be parsed as the expected type.
+    Invalid {
+        label: String,
+        kind: &'static str,
+        value: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing { label, kind } => write!(f, "Missing `{label}` (expected {kind})."),
+            Self::Invalid {
+                label,
+                kind,
+                value,
+                reason,
+            } => write!(
+                f,
+                "`{value}` is not a valid `{label}`, expected {kind} ({reason})."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
@@ -0,0 +1,132 @@
+/// Defines a command as a single async function, generating a function of the same name which
+/// returns the registered `Command`. Top level commands are registered by listing that function
+/// in the `top_level` of the module defining them.
+///
+/// The doc comment becomes the description, `#[category = ..]` is required, and
+/// `#[aliases = [..]]`, `#[permissions = MANAGE_GUILD | ..]`, `#[cooldown(User, 5)]`, in seconds,
+/// `#[guild_only = true]` and `#[takes_file = true]`, for commands reading `ctx.attachments`,
+/// are optional.
+/// Every parameter after the state and context is an argument, parsed according to its
+/// `Parameter` type and labelled with its name.
+///
+/// ```ignore
+/// command! {
+///     /// Remove the song at a position in the queue.
+///     #[category = Music]
+///     #[aliases = ["rm"]]
+///     pub async fn remove(s: State, ctx: Context, index: u32) -> anyhow::Result<()> {
+///         ...
+///     }
+/// }
+/// ```
+///
+/// A command which only groups subcommands lists the functions generating them instead.
+/// A group's permissions are required by all of its subcommands too, and a guild only group's
+/// subcommands are guild only as well. `#[default = ..]` names the subcommand which runs when
+/// the group is followed by anything other than a subcommand.
+///
+/// ```ignore
+/// command! {
+///     /// Manage this server's command aliases.
+///     #[category = Config]
+///     pub group alias { add, remove, list }
+/// }
+/// ```
+macro_rules! command {
+    (
+        $(#[doc = $doc:literal])*
+        #[category = $category:ident]
+        $(#[aliases = [$($alias:literal),* $(,)?]])?
+        $(#[permissions = $($permission:ident)|+])?
+        $(#[cooldown($bucket:ident, $seconds:literal)])?
+        $(#[guild_only = $guild_only:literal])?
+        $(#[takes_file = $takes_file:literal])?
+        $vis:vis async fn $name:ident(
+            $s:ident: State,
+            $ctx:ident: Context
+            $(, $arg:ident: $ty:ty)* $(,)?
+        ) -> anyhow::Result<()> $body:block
+    ) => {
+        $vis fn $name() -> $crate::parser::Command {
+            fn run(
+                $s: $crate::State,
+                $ctx: $crate::context::Context,
+                c: $crate::parser::CommandWithData,
+            ) -> ::std::pin::Pin<
+                Box<dyn ::std::future::Future<Output = anyhow::Result<()>> + Send + 'static>,
+            > {
+                Box::pin(async move {
+                    // Unused by commands which take no arguments.
+                    #[allow(unused_mut, unused_variables)]
+                    let mut arguments = c
+                        .arguments
+                        .map(Vec::from)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .peekable();
+                    $(
+                        let Some($arg) =
+                            <$ty as $crate::parser::Parameter>::take(&mut arguments)
+                        else {
+                            anyhow::bail!(
+                                "`{}` is missing its `{}` argument",
+                                stringify!($name),
+                                stringify!($arg),
+                            );
+                        };
+                    )*
+                    $body
+                })
+            }
+
+            $crate::parser::Command::new(
+                String::from(stringify!($name)),
+                Some(run),
+                &[],
+                &[$(<$ty as $crate::parser::Parameter>::argument(stringify!($arg))),*],
+            )
+            .aliases(&[$($($alias),*)?])
+            .description(&[$($doc),*].map(str::trim).join(" "))
+            .category($crate::parser::Category::$category)
+            .permissions(
+                ::twilight_model::guild::Permissions::empty()
+                    $($(| ::twilight_model::guild::Permissions::$permission)+)?
+            )
+            $(.cooldown(
+                $crate::parser::Bucket::$bucket,
+                ::std::time::Duration::from_secs($seconds),
+            ))?
+            $(.guild_only($guild_only))?
+            $(.takes_file($takes_file))?
+        }
+    };
+    (
+        $(#[doc = $doc:literal])*
+        #[category = $category:ident]
+        $(#[aliases = [$($alias:literal),* $(,)?]])?
+        $(#[permissions = $($permission:ident)|+])?
+        $(#[guild_only = $guild_only:literal])?
+        $(#[default = $default:ident])?
+        $vis:vis group $name:ident { $($subcommand:path),* $(,)? }
+    ) => {
+        $vis fn $name() -> $crate::parser::Command {
+            $crate::parser::Command::new(
+                String::from(stringify!($name)),
+                None,
+                &[$($subcommand()),*],
+                &[],
+            )
+            .aliases(&[$($($alias),*)?])
+            .description(&[$($doc),*].map(str::trim).join(" "))
+            .category($crate::parser::Category::$category)
+            .permissions(
+                ::twilight_model::guild::Permissions::empty()
+                    $($(| ::twilight_model::guild::Permissions::$permission)+)?
+            )
+            $(.guild_only($guild_only))?
+            $(.default_subcommand(stringify!($default)))?
+        }
+    };
+}
+
+pub(crate) use command;
@@ -1,10 +1,24 @@
 mod argument;
 mod command;
+mod error;
+mod macros;
+mod parse;
+pub mod slash;
+mod suggest;
 mod textcommand;
 
 pub use argument::Argument;
-pub use argument::ArgumentMetadata;
 pub use argument::ArgumentWithData;
+pub use argument::Parameter;
+pub use argument::Rest;
+pub use command::permission_names;
+pub use command::Bucket;
+pub use command::Category;
 pub use command::Command;
 pub use command::CommandWithData;
+pub use command::Cooldown;
+pub use error::ParseError;
+pub use suggest::suggest;
 pub use textcommand::TextCommand;
+
+pub(crate) use macros::command;
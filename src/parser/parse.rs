@@ -0,0 +1,218 @@
+use std::time::Duration;
+
+use anyhow::anyhow;
+use reqwest::Url;
+use twilight_model::{
+    channel::message::EmojiReactionType,
+    id::{
+        marker::{ChannelMarker, RoleMarker, UserMarker},
+        Id,
+    },
+};
+
+/// Parses either a raw snowflake, or a mention made of one of `prefixes`, the id, and `>`.
+fn mention<T>(val: &str, prefixes: &[&str]) -> Option<Id<T>> {
+    let id = prefixes
+        .iter()
+        .find_map(|p| val.strip_prefix(p)?.strip_suffix('>'))
+        .unwrap_or(val);
+    id.parse().ok()
+}
+
+/// `<@id>`, `<@!id>` or a raw id.
+pub fn user(val: &str) -> anyhow::Result<Id<UserMarker>> {
+    mention(val, &["<@!", "<@"]).ok_or_else(|| anyhow!("`{val}` is not a user"))
+}
+
+/// `<#id>` or a raw id.
+pub fn channel(val: &str) -> anyhow::Result<Id<ChannelMarker>> {
+    mention(val, &["<#"]).ok_or_else(|| anyhow!("`{val}` is not a channel"))
+}
+
+/// `<@&id>` or a raw id.
+pub fn role(val: &str) -> anyhow::Result<Id<RoleMarker>> {
+    mention(val, &["<@&"]).ok_or_else(|| anyhow!("`{val}` is not a role"))
+}
+
+/// `<:name:id>`, `<a:name:id>`, or a unicode emoji.
+pub fn emoji(val: &str) -> anyhow::Result<EmojiReactionType> {
+    if let Some(inner) = val.strip_prefix('<').and_then(|v| v.strip_suffix('>')) {
+        let (animated, inner) = inner
+            .strip_prefix("a:")
+            .map_or_else(|| (false, inner.strip_prefix(':')), |i| (true, Some(i)));
+        if let Some((name, id)) = inner.and_then(|i| i.split_once(':')) {
+            if let Ok(id) = id.parse() {
+                return Ok(EmojiReactionType::Custom {
+                    animated,
+                    id,
+                    name: Some(String::from(name)),
+                });
+            }
+        }
+    } else if !val.is_empty() && !val.is_ascii() && val.chars().all(|c| !c.is_alphanumeric()) {
+        return Ok(EmojiReactionType::Unicode {
+            name: String::from(val),
+        });
+    }
+    Err(anyhow!("`{val}` is not an emoji"))
+}
+
+/// An absolute `http` or `https` URL, optionally wrapped in `<>` to suppress embeds.
+pub fn url(val: &str) -> anyhow::Result<Url> {
+    let val = val
+        .strip_prefix('<')
+        .and_then(|v| v.strip_suffix('>'))
+        .unwrap_or(val);
+    let url = Url::parse(val)?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        anyhow::bail!("`{val}` is not a web link");
+    }
+    Ok(url)
+}
+
+/// A human duration such as `1h30m`, `2d`, `1w 3d` or `90s`. A bare number is taken as seconds.
+pub fn duration(val: &str) -> anyhow::Result<Duration> {
+    if let Ok(secs) = val.parse() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = val.trim();
+    if rest.is_empty() {
+        anyhow::bail!("`{val}` is not a duration");
+    }
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let amount: u64 = rest[..digits]
+            .parse()
+            .map_err(|_| anyhow!("`{val}` is not a duration"))?;
+        rest = &rest[digits..];
+
+        let unit_len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let unit = match &rest[..unit_len] {
+            "w" | "wk" | "week" | "weeks" => Duration::from_secs(60 * 60 * 24 * 7),
+            "d" | "day" | "days" => Duration::from_secs(60 * 60 * 24),
+            "h" | "hr" | "hour" | "hours" => Duration::from_secs(60 * 60),
+            "m" | "min" | "mins" | "minute" | "minutes" => Duration::from_secs(60),
+            "s" | "sec" | "secs" | "second" | "seconds" => Duration::from_secs(1),
+            unit => anyhow::bail!("`{unit}` is not a unit of time"),
+        };
+        rest = rest[unit_len..].trim_start();
+
+        total = u32::try_from(amount)
+            .ok()
+            .and_then(|amount| unit.checked_mul(amount))
+            .and_then(|d| total.checked_add(d))
+            .ok_or_else(|| anyhow!("`{val}` is too long"))?;
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use twilight_model::{channel::message::EmojiReactionType, id::Id};
+
+    use super::{channel, duration, emoji, role, url, user};
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(duration("1h30m").unwrap(), Duration::from_secs(90 * 60));
+        assert_eq!(
+            duration("1w 3d").unwrap(),
+            Duration::from_secs(10 * 24 * 60 * 60)
+        );
+        assert_eq!(
+            duration("2hours").unwrap(),
+            Duration::from_secs(2 * 60 * 60)
+        );
+    }
+
+    #[test]
+    fn rejects_bad_durations() {
+        for val in ["", "  ", "h", "5x", "1h30", "-5m"] {
+            assert!(duration(val).is_err(), "{val:?} should not parse");
+        }
+    }
+
+    #[test]
+    fn rejects_overflowing_durations() {
+        let error = duration("99999999999w").unwrap_err();
+        assert!(error.to_string().contains("too long"));
+        // Too many digits to even be a number.
+        assert!(duration("99999999999999999999999s").is_err());
+    }
+
+    #[test]
+    fn parses_mentions_and_raw_snowflakes() {
+        assert_eq!(user("<@123>").unwrap(), Id::new(123));
+        assert_eq!(user("<@!123>").unwrap(), Id::new(123));
+        assert_eq!(user("123").unwrap(), Id::new(123));
+        assert_eq!(channel("<#456>").unwrap(), Id::new(456));
+        assert_eq!(role("<@&789>").unwrap(), Id::new(789));
+        assert_eq!(role("789").unwrap(), Id::new(789));
+    }
+
+    #[test]
+    fn rejects_bad_mentions() {
+        assert!(user("<#123>").is_err());
+        assert!(channel("<@123>").is_err());
+        assert!(user("0").is_err());
+        assert!(user("someone").is_err());
+    }
+
+    #[test]
+    fn parses_custom_emoji() {
+        let EmojiReactionType::Custom { animated, id, name } = emoji("<:wave:42>").unwrap() else {
+            panic!("expected a custom emoji");
+        };
+        assert!(!animated);
+        assert_eq!(id, Id::new(42));
+        assert_eq!(name.as_deref(), Some("wave"));
+
+        let EmojiReactionType::Custom { animated, id, .. } = emoji("<a:dance:43>").unwrap() else {
+            panic!("expected a custom emoji");
+        };
+        assert!(animated);
+        assert_eq!(id, Id::new(43));
+    }
+
+    #[test]
+    fn parses_unicode_emoji() {
+        let EmojiReactionType::Unicode { name } = emoji("👍").unwrap() else {
+            panic!("expected a unicode emoji");
+        };
+        assert_eq!(name, "👍");
+    }
+
+    #[test]
+    fn rejects_bad_emoji() {
+        for val in [
+            "",
+            "wave",
+            ":wave:",
+            "<:wave:>",
+            "<wave:42>",
+            "<:wave:abc>",
+            "é",
+        ] {
+            assert!(emoji(val).is_err(), "{val:?} should not parse");
+        }
+    }
+
+    #[test]
+    fn parses_urls() {
+        assert_eq!(
+            url("<https://example.com/a>").unwrap().as_str(),
+            "https://example.com/a"
+        );
+        assert!(url("ftp://example.com").is_err());
+        assert!(url("example.com").is_err());
+    }
+}
@@ -0,0 +1,292 @@
+use twilight_model::{
+    application::{
+        command::{
+            Command as ApplicationCommand, CommandOption, CommandOptionType,
+            CommandOptionValue as OptionBound, CommandType,
+        },
+        interaction::application_command::{CommandData, CommandDataOption, CommandOptionValue},
+    },
+    id::Id,
+};
+
+use super::{
+    command::parse_argument, Argument, ArgumentWithData, Command, CommandWithData, ParseError,
+    TextCommand,
+};
+
+/// Discord only allows lowercase names of up to 32 characters without spaces.
+fn option_name(label: &str) -> String {
+    label
+        .to_lowercase()
+        .replace(' ', "_")
+        .chars()
+        .take(32)
+        .collect()
+}
+
+/// Discord requires descriptions between 1 and 100 characters.
+fn option_description(description: &str, fallback: &str) -> String {
+    let description = if description.is_empty() {
+        fallback
+    } else {
+        description
+    };
+    description.chars().take(100).collect()
+}
+
+fn argument_option(argument: &Argument) -> CommandOption {
+    let size = argument.clone().size();
+    let kind = match argument {
+        Argument::UInt(_) | Argument::Int(_) if size != 0 => CommandOptionType::Integer,
+        Argument::Bool(_) if size != 0 => CommandOptionType::Boolean,
+        Argument::User(_) if size != 0 => CommandOptionType::User,
+        Argument::Channel(_) if size != 0 => CommandOptionType::Channel,
+        Argument::Role(_) if size != 0 => CommandOptionType::Role,
+        _ => CommandOptionType::String,
+    };
+    // Discord allows much larger integers than the arguments hold.
+    let bounds = |min: i64, max: i64| {
+        (
+            Some(OptionBound::Integer(min)),
+            Some(OptionBound::Integer(max)),
+        )
+    };
+    let (min_value, max_value) = match argument {
+        Argument::UInt(_) if size != 0 => bounds(0, u32::MAX.into()),
+        Argument::Int(_) if size != 0 => bounds(i32::MIN.into(), i32::MAX.into()),
+        _ => (None, None),
+    };
+
+    CommandOption {
+        autocomplete: None,
+        channel_types: None,
+        choices: None,
+        description: option_description("", argument.kind()),
+        description_localizations: None,
+        kind,
+        max_length: None,
+        max_value,
+        min_length: None,
+        min_value,
+        name: option_name(&argument.clone().label()),
+        name_localizations: None,
+        options: None,
+        required: Some(argument.required()),
+    }
+}
+
+/// The option slash commands are given a file through, see `Context::attachments`.
+fn file_option() -> CommandOption {
+    CommandOption {
+        autocomplete: None,
+        channel_types: None,
+        choices: None,
+        description: String::from("The file to use"),
+        description_localizations: None,
+        kind: CommandOptionType::Attachment,
+        max_length: None,
+        max_value: None,
+        min_length: None,
+        min_value: None,
+        name: String::from("file"),
+        name_localizations: None,
+        options: None,
+        required: Some(true),
+    }
+}
+
+/// The options of a command: its subcommands if it has any, otherwise its arguments.
+/// Discord only allows a command, a group and a subcommand, so deeper subcommands are left out.
+fn command_options(cmd: &Command, depth: usize) -> Vec<CommandOption> {
+    let subcommands = cmd.subcommands.iter().flatten().collect::<Vec<_>>();
+    if subcommands.is_empty() {
+        let mut arguments = cmd.arguments.iter().flatten().collect::<Vec<_>>();
+        arguments.sort_by_key(|a| !a.required());
+        let mut options = arguments
+            .into_iter()
+            .map(argument_option)
+            .collect::<Vec<_>>();
+        if cmd.takes_file {
+            // Required options have to come first.
+            options.insert(0, file_option());
+        }
+        return options;
+    }
+
+    subcommands
+        .into_iter()
+        .filter(|sc| depth < 2 || sc.subcommands.iter().flatten().next().is_none())
+        .map(|sc| {
+            let is_group = sc.subcommands.iter().flatten().next().is_some();
+            CommandOption {
+                autocomplete: None,
+                channel_types: None,
+                choices: None,
+                description: option_description(&sc.description, &sc.name),
+                description_localizations: None,
+                kind: if is_group {
+                    CommandOptionType::SubCommandGroup
+                } else {
+                    CommandOptionType::SubCommand
+                },
+                max_length: None,
+                max_value: None,
+                min_length: None,
+                min_value: None,
+                name: option_name(&sc.name),
+                name_localizations: None,
+                options: Some(command_options(sc, depth + 1)),
+                required: None,
+            }
+        })
+        .collect()
+}
+
+/// Builds the application commands to register for every top level command in `root`.
+#[allow(deprecated)]
+pub fn application_commands(root: &Command) -> Vec<ApplicationCommand> {
+    root.subcommands
+        .iter()
+        .flatten()
+        .map(|cmd| ApplicationCommand {
+            application_id: None,
+            contexts: None,
+            default_member_permissions: (!cmd.permissions.is_empty()).then_some(cmd.permissions),
+            dm_permission: Some(!cmd.guild_only),
+            description: option_description(&cmd.description, &cmd.name),
+            description_localizations: None,
+            guild_id: None,
+            id: None,
+            integration_types: None,
+            kind: CommandType::ChatInput,
+            name: option_name(&cmd.name),
+            name_localizations: None,
+            nsfw: None,
+            options: command_options(cmd, 1),
+            version: Id::new(1),
+        })
+        .collect()
+}
+
+fn option_value(value: &CommandOptionValue) -> Option<String> {
+    Some(match value {
+        CommandOptionValue::String(s) => s.clone(),
+        CommandOptionValue::Integer(i) => i.to_string(),
+        CommandOptionValue::Number(n) => n.to_string(),
+        CommandOptionValue::Boolean(b) => b.to_string(),
+        CommandOptionValue::User(id) => id.to_string(),
+        CommandOptionValue::Channel(id) => id.to_string(),
+        CommandOptionValue::Role(id) => id.to_string(),
+        CommandOptionValue::Mentionable(id) => id.to_string(),
+        _ => return None,
+    })
+}
+
+/// Finds the command an interaction invoked, following its subcommand options down the tree.
+/// Returns the command, the names of its parents, e.g. `"alias "`, and the options given to it.
+pub fn resolve(
+    root: &Command,
+    data: &CommandData,
+) -> Option<(Box<Command>, String, Vec<CommandDataOption>)> {
+    let mut cmd = root
+        .subcommands
+        .iter()
+        .flatten()
+        .find(|c| option_name(&c.name) == data.name)
+        .map(|c| Box::new(c.clone()))?;
+    let mut parents = String::new();
+    let mut options = data.options.clone();
+
+    while let Some((name, sub_options)) = options.iter().find_map(|o| match &o.value {
+        CommandOptionValue::SubCommand(opts) | CommandOptionValue::SubCommandGroup(opts) => {
+            Some((o.name.clone(), opts.clone()))
+        }
+        _ => None,
+    }) {
+        let Some(subcommand) = cmd
+            .subcommands
+            .iter()
+            .flatten()
+            .find(|c| option_name(&c.name) == name)
+            .map(|c| Box::new(c.clone()))
+        else {
+            break;
+        };
+        parents += &format!("{} ", cmd.name);
+        cmd = subcommand;
+        options = sub_options;
+    }
+
+    Some((cmd, parents, options))
+}
+
+impl CommandWithData {
+    /// Parses the options of a slash command against `c`'s arguments.
+    /// A variadic (`size: 0`) argument is given as a single string, which is split into words.
+    pub fn from_options(
+        c: Command,
+        options: &[CommandDataOption],
+    ) -> Result<CommandWithData, ParseError> {
+        let mut collected_args: Vec<ArgumentWithData> = vec![];
+        for argument in c.arguments.iter().flatten() {
+            let name = option_name(&argument.clone().label());
+            let Some(value) = options
+                .iter()
+                .find(|o| o.name == name)
+                .and_then(|o| option_value(&o.value))
+            else {
+                if argument.required() {
+                    return Err(ParseError::Missing {
+                        label: argument.clone().label(),
+                        kind: argument.kind(),
+                    });
+                }
+                continue;
+            };
+
+            if argument.clone().size() == 0 && !matches!(argument, Argument::Rest(_)) {
+                // Split the way a text command would be, so quotes keep words together.
+                for word in TextCommand::new(&value) {
+                    collected_args.push(parse_argument(argument, word)?);
+                }
+            } else {
+                collected_args.push(parse_argument(argument, value)?);
+            }
+        }
+
+        Ok(CommandWithData {
+            arguments: Some(collected_args.into()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use twilight_model::application::interaction::application_command::{
+        CommandDataOption, CommandOptionValue,
+    };
+
+    use super::super::{ArgumentWithData, Command, CommandWithData, Parameter};
+
+    #[test]
+    fn splits_words_options_the_way_text_commands_are() {
+        let words = <Vec<String> as Parameter>::argument("words");
+        let cmd = Command::new(String::from("echo"), None, &[], &[words]);
+        let options = [CommandDataOption {
+            name: String::from("words"),
+            value: CommandOptionValue::String(String::from(r#"one "two words""#)),
+        }];
+
+        let parsed = CommandWithData::from_options(cmd, &options).unwrap();
+        let words = parsed
+            .arguments
+            .iter()
+            .flatten()
+            .map(|a| match a {
+                ArgumentWithData::String(s) => s.as_str(),
+                _ => panic!("words should be strings"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(words, ["one", "two words"]);
+    }
+}
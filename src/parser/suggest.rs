@@ -0,0 +1,110 @@
+/// The most suggestions given for a single unknown command.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// How many single character insertions, deletions, substitutions or swaps of two neighbouring
+/// characters it takes to turn `a` into `b`, so `plya` is one edit away from `play`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+
+    // Only the last two rows are needed to find a swap.
+    let mut before = vec![0; b.len() + 1];
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut before, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// The candidates closest to `name`, best first.
+/// Candidates which need more than a third of `name` changed are left out, so short or unrelated
+/// words get no suggestions at all.
+pub fn suggest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    let max_distance = name.chars().count() / 3;
+
+    let mut scored = candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect::<Vec<_>>();
+    scored.sort_unstable();
+    scored.dedup_by_key(|(_, candidate)| *candidate);
+
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{edit_distance, suggest};
+
+    const COMMANDS: [&str; 6] = ["play", "pause", "queue", "skip", "jump", "help"];
+
+    #[test]
+    fn counts_single_edits() {
+        assert_eq!(edit_distance("play", "play"), 0);
+        assert_eq!(edit_distance("play", "pla"), 1);
+        assert_eq!(edit_distance("play", "plays"), 1);
+        assert_eq!(edit_distance("play", "pray"), 1);
+        assert_eq!(edit_distance("", "play"), 4);
+        assert_eq!(edit_distance("play", ""), 4);
+    }
+
+    #[test]
+    fn counts_swaps_as_one_edit() {
+        assert_eq!(edit_distance("plya", "play"), 1);
+        assert_eq!(edit_distance("ab", "ba"), 1);
+        assert_eq!(edit_distance("pasue", "pause"), 1);
+        assert_eq!(edit_distance("lpya", "play"), 2);
+    }
+
+    #[test]
+    fn suggests_close_commands() {
+        assert_eq!(suggest("plya", COMMANDS), ["play"]);
+        assert_eq!(suggest("pasue", COMMANDS), ["pause"]);
+        assert_eq!(suggest("skpi", COMMANDS), ["skip"]);
+    }
+
+    #[test]
+    fn allows_a_third_of_the_name_to_change() {
+        // 3 and 4 characters both allow 1 edit.
+        assert_eq!(suggest("queu", COMMANDS), ["queue"]);
+        assert!(suggest("que", COMMANDS).is_empty());
+        // 7 and 8 characters both allow 2 edits.
+        assert_eq!(suggest("queuexy", ["queue"]), ["queue"]);
+        assert!(suggest("queuexyz", ["queue"]).is_empty());
+    }
+
+    #[test]
+    fn gives_at_most_three_suggestions_best_first() {
+        let candidates = ["abcdxy", "abcdez", "abcdef", "abcdeg", "abcdex", "abcdef"];
+        assert_eq!(
+            suggest("abcdef", candidates),
+            ["abcdef", "abcdeg", "abcdex"]
+        );
+    }
+
+    #[test]
+    fn suggests_nothing_for_short_or_unrelated_names() {
+        assert!(suggest("p", COMMANDS).is_empty());
+        assert!(suggest("pl", COMMANDS).is_empty());
+        assert!(suggest("weather", COMMANDS).is_empty());
+        assert!(suggest("", COMMANDS).is_empty());
+    }
+}
@@ -1,21 +1,105 @@
+/// `TextCommand` splits a message into words, the way a shell would.
+/// - Whitespace separates words.
+/// - `"double"` and `'single'` quotes group words, a backslash escapes the next character
+///   outside of quotes and inside double quotes.
+/// - `` `code spans` `` and ```` ```code blocks``` ```` group words verbatim, with no escapes.
+///
+/// Quotes and code spans only open at the start of a word, so the apostrophe in `don't` stays
+/// literal, and a quote or code span that is never closed is treated as literal text.
+///
+/// The byte offset of every word into the original message is kept, so the rest of the message
+/// can be taken verbatim with `rest`.
 #[derive(Clone)]
-pub struct TextCommand<'a> {
-    words: Box<[&'a str]>,
+pub struct TextCommand {
+    message: Box<str>,
+    words: Box<[(usize, String)]>,
     ptr: usize,
 }
-impl<'a> TextCommand<'a> {
-    pub fn new(message: &str) -> TextCommand {
-        TextCommand {
-            words: message.split_whitespace().collect(),
+
+const GROUPS: [(&str, bool); 4] = [("```", false), ("`", false), ("\"", true), ("'", false)];
+
+/// Reads a quoted string or code span from the start of `s`, if it is closed.
+fn grouped(s: &str) -> Option<(String, &str)> {
+    let (delim, escapes) = GROUPS.into_iter().find(|(d, _)| s.starts_with(d))?;
+    let body = &s[delim.len()..];
+
+    let mut res = String::new();
+    let mut chars = body.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if body[i..].starts_with(delim) {
+            return Some((res, &body[i + delim.len()..]));
+        }
+        if escapes && c == '\\' {
+            if let Some((_, escaped)) = chars.next() {
+                res.push(escaped);
+                continue;
+            }
+        }
+        res.push(c);
+    }
+    None
+}
+
+/// Reads one word from the start of `s`, returning it and whatever follows it.
+fn next_word(s: &str) -> (String, &str) {
+    let (mut word, rest) = grouped(s).unwrap_or_else(|| (String::new(), s));
+
+    let mut chars = rest.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c.is_whitespace() {
+            return (word, &rest[i..]);
+        }
+        if c == '\\' {
+            if let Some((_, escaped)) = chars.next_if(|(_, e)| !e.is_whitespace()) {
+                word.push(escaped);
+                continue;
+            }
+        }
+        word.push(c);
+    }
+    (word, "")
+}
+
+fn tokenize(message: &str) -> Vec<(usize, String)> {
+    let mut words = vec![];
+    let mut rest = message.trim_start();
+    while !rest.is_empty() {
+        let offset = message.len() - rest.len();
+        let (word, remaining) = next_word(rest);
+        words.push((offset, word));
+        rest = remaining.trim_start();
+    }
+    words
+}
+
+impl TextCommand {
+    pub fn new(message: &str) -> Self {
+        Self {
+            message: message.into(),
+            words: tokenize(message).into(),
             ptr: 0,
         }
     }
 
-    pub fn first(&self) -> &'a str {
-        self.words[0]
+    pub fn first(&self) -> &str {
+        &self.words[0].1
+    }
+
+    /// The next word, without consuming it.
+    pub fn peek(&self) -> Option<&str> {
+        self.words.get(self.ptr).map(|(_, word)| word.as_str())
+    }
+
+    /// Takes everything from the next word to the end of the message exactly as it was written,
+    /// keeping newlines, repeated spaces, quotes and code blocks intact.
+    pub fn rest(&mut self) -> Option<String> {
+        let (offset, _) = self.words.get(self.ptr)?;
+        let rest = String::from(self.message[*offset..].trim_end());
+        self.ptr = self.words.len();
+        Some(rest)
     }
 }
-impl Iterator for TextCommand<'_> {
+impl Iterator for TextCommand {
     type Item = String;
 
     fn next(&mut self) -> Option<String> {
@@ -23,9 +107,77 @@ impl Iterator for TextCommand<'_> {
             return None;
         }
 
-        let word = Some(String::from(self.words[self.ptr]));
+        let word = Some(self.words[self.ptr].1.clone());
         self.ptr += 1;
 
         word
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::TextCommand;
+
+    fn words(message: &str) -> Vec<String> {
+        TextCommand::new(message).collect()
+    }
+
+    #[test]
+    fn splits_on_whitespace() {
+        assert_eq!(words("  play   some\tsong\n"), ["play", "some", "song"]);
+    }
+
+    #[test]
+    fn groups_quoted_words() {
+        assert_eq!(
+            words(r#"say "hello there" 'good bye'"#),
+            ["say", "hello there", "good bye"]
+        );
+    }
+
+    #[test]
+    fn escapes_inside_double_quotes_only() {
+        assert_eq!(words(r#""a \"quote\" \\ here""#), [r#"a "quote" \ here"#]);
+        assert_eq!(words(r"'no \escapes'"), [r"no \escapes"]);
+    }
+
+    #[test]
+    fn escapes_outside_quotes() {
+        assert_eq!(words(r#"\"x y\'"#), ["\"x", "y'"]);
+        // Whitespace can't be escaped, the backslash stays.
+        assert_eq!(words(r"one\ word"), ["one\\", "word"]);
+        assert_eq!(words(r"trailing\"), ["trailing\\"]);
+    }
+
+    #[test]
+    fn unclosed_quotes_are_literal() {
+        assert_eq!(words(r#"say "hello there"#), ["say", "\"hello", "there"]);
+        assert_eq!(words("don't stop"), ["don't", "stop"]);
+    }
+
+    #[test]
+    fn code_is_verbatim() {
+        assert_eq!(words(r"eval `a \n b`"), ["eval", r"a \n b"]);
+        assert_eq!(
+            words("eval ```rust\nfn main() {}\n```"),
+            ["eval", "rust\nfn main() {}\n"]
+        );
+    }
+
+    #[test]
+    fn rest_is_verbatim_after_a_quoted_word() {
+        let mut cmd = TextCommand::new(r#"remind "in 5m"   take  "the" bread  "#);
+        assert_eq!(cmd.next().as_deref(), Some("remind"));
+        assert_eq!(cmd.next().as_deref(), Some("in 5m"));
+        assert_eq!(cmd.rest().as_deref(), Some(r#"take  "the" bread"#));
+        assert_eq!(cmd.next(), None);
+        assert_eq!(cmd.rest(), None);
+    }
+
+    #[test]
+    fn rest_includes_quotes_of_the_next_word() {
+        let mut cmd = TextCommand::new(r#"say "hello there""#);
+        cmd.next();
+        assert_eq!(cmd.rest().as_deref(), Some(r#""hello there""#));
+    }
+}
@@ -0,0 +1,61 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use tokio::sync::Mutex;
+use twilight_model::id::{marker::MessageMarker, Id};
+
+/// How long after a text command is used editing its message runs it again.
+const EDIT_WINDOW: Duration = Duration::from_secs(120);
+
+struct Invocation {
+    content: String,
+    at: Instant,
+    /// The bot's first reply, edited instead of replying again when the command is re-run.
+    response: Option<Id<MessageMarker>>,
+}
+
+/// Recently used text commands and what the bot answered them with.
+#[derive(Default)]
+pub struct Responses {
+    invocations: Mutex<HashMap<Id<MessageMarker>, Invocation>>,
+}
+
+impl Responses {
+    /// Remembers that `message` invoked a command, forgetting ones too old to be edited.
+    pub async fn invoked(&self, message: Id<MessageMarker>, content: &str) {
+        let mut invocations = self.invocations.lock().await;
+        invocations.retain(|_, invocation| invocation.at.elapsed() < EDIT_WINDOW);
+        invocations
+            .entry(message)
+            .and_modify(|invocation| invocation.content = String::from(content))
+            .or_insert_with(|| Invocation {
+                content: String::from(content),
+                at: Instant::now(),
+                response: None,
+            });
+    }
+
+    /// Whether an edit of `message` should run it again, which it should when it invoked a
+    /// command recently and its content actually changed, rather than e.g. an embed being added.
+    pub async fn edited(&self, message: Id<MessageMarker>, content: &str) -> bool {
+        self.invocations
+            .lock()
+            .await
+            .get(&message)
+            .is_some_and(|i| i.at.elapsed() < EDIT_WINDOW && i.content != content)
+    }
+
+    /// The bot's first reply to `message`, if it still remembers it.
+    pub async fn response(&self, message: Id<MessageMarker>) -> Option<Id<MessageMarker>> {
+        self.invocations.lock().await.get(&message)?.response
+    }
+
+    /// Remembers `response` as the bot's first reply to `message`.
+    pub async fn responded(&self, message: Id<MessageMarker>, response: Id<MessageMarker>) {
+        if let Some(invocation) = self.invocations.lock().await.get_mut(&message) {
+            invocation.response = Some(response);
+        }
+    }
+}
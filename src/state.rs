@@ -1,30 +1,43 @@
-use std::{collections::HashMap, str::pattern::Pattern, sync::Arc, time::Duration};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use reqwest::Client;
 use songbird::Songbird;
-use tokio::{sync::Mutex, time::sleep};
+use tokio::{
+    sync::{Mutex, OwnedMutexGuard},
+    time::sleep,
+};
 use twilight_cache_inmemory::InMemoryCache;
 use twilight_gateway::Event;
 use twilight_http::Client as HttpClient;
 use twilight_model::{
-    channel::message::EmojiReactionType,
+    application::interaction::InteractionData,
+    channel::{message::EmojiReactionType, Message},
     id::{marker::GuildMarker, Id},
 };
+use twilight_standby::Standby;
 
 use crate::{
-    config::servers::ServerConfig,
+    commands::describe,
+    config::{global::BotConfig, servers::ServerConfig},
+    context::Context,
+    cooldowns::{Check, Cooldowns},
+    error,
     music::Queue,
-    parser::{Command, CommandWithData, TextCommand},
+    parser::{permission_names, slash, suggest, Command, CommandWithData, ParseError, TextCommand},
+    responses::Responses,
+    storage::{Backend, Storage},
 };
 
 pub trait Handler {
     async fn handle_event(self, event: Event) -> anyhow::Result<()>;
+    async fn handle_message(self, msg: &Message) -> anyhow::Result<()>;
 
     async fn leave_empty_vcs(self) -> anyhow::Result<()>;
     async fn check_done_vcs(self) -> anyhow::Result<()>;
+    async fn purge_departed_guilds(self) -> anyhow::Result<()>;
 
     async fn generate_configs(self) -> anyhow::Result<()>;
-    async fn write_configs_to_file(&self) -> anyhow::Result<()>;
+    async fn register_commands(&self) -> anyhow::Result<()>;
     async fn read_configs_from_file(&self) -> anyhow::Result<()>;
 }
 
@@ -34,8 +47,17 @@ pub struct StateRef<'a> {
     pub songbird: Songbird,
     pub vcs: Mutex<HashMap<Id<GuildMarker>, Arc<Mutex<Queue<'a>>>>>,
     pub server_configs: Mutex<HashMap<Id<GuildMarker>, ServerConfig>>,
+    /// One lock per guild, held from changing its config until the change is saved.
+    config_saves: Mutex<HashMap<Id<GuildMarker>, Arc<Mutex<()>>>>,
     pub client: Client,
     pub cache: InMemoryCache,
+    pub storage: Backend,
+    pub cooldowns: Cooldowns,
+    pub responses: Responses,
+    /// Lets commands wait for the author's next message, e.g. to confirm a change.
+    pub standby: Standby,
+    /// Settings for the bot as a whole, read at startup.
+    pub bot: BotConfig,
 }
 
 pub type State = Arc<StateRef<'static>>;
@@ -67,31 +89,203 @@ async fn get_empty_vcs(state: State) -> Vec<Id<GuildMarker>> {
     guilds
 }
 
+/// Whether the author is one of the bot's owners, who are exempt from permissions and cooldowns.
+fn is_owner(ctx: &Context) -> bool {
+    ctx.state.bot.owners.contains(&ctx.author.id)
+}
+
+/// Replies with what the author is missing if they may not use `cmd`, returning whether they may.
+async fn check_permissions(ctx: &Context, cmd: &Command) -> anyhow::Result<bool> {
+    if is_owner(ctx) {
+        return Ok(true);
+    }
+    let Some(permissions) = ctx.permissions().await else {
+        ctx.reply(&format!(
+            "I couldn't check whether you may use `{}`, try again in a moment.",
+            cmd.name
+        ))
+        .await?;
+        return Ok(false);
+    };
+    let missing = cmd.permissions - permissions;
+    if missing.is_empty() {
+        return Ok(true);
+    }
+    ctx.reply(&format!(
+        "You need the {} permission{} to use `{}`.",
+        permission_names(missing),
+        if missing.bits().count_ones() == 1 {
+            ""
+        } else {
+            "s"
+        },
+        cmd.name
+    ))
+    .await?;
+    Ok(false)
+}
+
+/// Replies with an explanation if `cmd` only works in servers and was used outside of one,
+/// returning whether it may run.
+async fn check_guild(ctx: &Context, cmd: &Command, parents: &str) -> anyhow::Result<bool> {
+    if !cmd.guild_only || ctx.guild_id.is_some() {
+        return Ok(true);
+    }
+    ctx.reply(&format!(
+        "`{parents}{}` only works in servers, not in DMs.",
+        cmd.name
+    ))
+    .await?;
+    Ok(false)
+}
+
+/// The command which manages command rules, which is never restricted by them so it can't lock
+/// a server out.
+const RULES_COMMAND: &str = "commands";
+
+/// Replies with why the author may not use `cmd` if this server's command rules forbid it,
+/// returning whether they may.
+async fn check_rules(ctx: &Context, cmd: &Command, parents: &str) -> anyhow::Result<bool> {
+    let Some(guild) = ctx.guild_id else {
+        return Ok(true);
+    };
+    let top = parents.split_whitespace().next().unwrap_or(&cmd.name);
+    if top == RULES_COMMAND {
+        return Ok(true);
+    }
+
+    let denial = ctx
+        .state
+        .server_configs
+        .lock()
+        .await
+        .get(&guild)
+        .and_then(|config| {
+            config.command_denial(
+                top,
+                &cmd.category.name().to_lowercase(),
+                ctx.channel_id,
+                &ctx.roles,
+            )
+        });
+    let Some(denial) = denial else {
+        return Ok(true);
+    };
+    ctx.reply(&denial).await?;
+    Ok(false)
+}
+
+/// Replies once if `cmd` is cooling down for the author, returning whether it may run.
+async fn check_cooldown(ctx: &Context, cmd: &Command, parents: &str) -> anyhow::Result<bool> {
+    let Some(cooldown) = cmd.cooldown.filter(|_| !is_owner(ctx)) else {
+        return Ok(true);
+    };
+    let path = format!("{parents}{}", cmd.name);
+    match ctx.state.cooldowns.check(&path, cooldown, ctx).await {
+        Check::Ready => Ok(true),
+        Check::Cooling(left) => {
+            ctx.reply(&format!(
+                "`{path}` is on cooldown, try again in {}s.",
+                left.as_secs() + 1
+            ))
+            .await?;
+            Ok(false)
+        }
+        Check::Silenced => Ok(false),
+    }
+}
+
+/// Whether `word` mentions the bot, which works as a prefix in every server.
+fn mentions_bot(cache: &InMemoryCache, word: &str) -> bool {
+    let Some(me) = cache.current_user() else {
+        return false;
+    };
+    word.strip_prefix("<@")
+        .and_then(|w| w.strip_suffix('>'))
+        .map(|w| w.trim_start_matches('!'))
+        .is_some_and(|id| id == me.id.to_string())
+}
+
+/// How often configs of servers the bot was removed from are checked for expiry.
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Makes sure a server the bot is in has a config, restoring it if the bot had been removed.
+async fn guild_joined(state: &State, guild: Id<GuildMarker>) -> anyhow::Result<()> {
+    let _saving = state.lock_config(guild).await;
+    let mut configs = state.server_configs.lock().await;
+    let config = match configs.get_mut(&guild) {
+        Some(config) => {
+            if !config.restore() {
+                return Ok(());
+            }
+            tracing::info!("Added back to {guild}, restoring its config");
+            config.clone()
+        }
+        None => {
+            tracing::info!("Added to {guild}");
+            let config = ServerConfig::new();
+            configs.insert(guild, config.clone());
+            config
+        }
+    };
+    drop(configs);
+    state.storage.save_config(guild, &config).await
+}
+
+/// Stops playing in a server the bot was removed from and archives its config, which is deleted
+/// once `bot.config_grace` has passed.
+async fn guild_left(state: &State, guild: Id<GuildMarker>) -> anyhow::Result<()> {
+    state.vcs.lock().await.remove(&guild);
+    if state.songbird.get(guild).is_some() {
+        state.songbird.remove(guild).await?;
+    }
+
+    let _saving = state.lock_config(guild).await;
+    let mut configs = state.server_configs.lock().await;
+    // Archived configs keep the time the bot first left, and need no saving again.
+    let Some(config) = configs
+        .get_mut(&guild)
+        .filter(|config| config.left_at().is_none())
+    else {
+        return Ok(());
+    };
+    config.archive();
+    let config = config.clone();
+    drop(configs);
+    tracing::info!("Removed from {guild}, archiving its config");
+    state.storage.save_config(guild, &config).await
+}
+
 impl Handler for State {
     async fn generate_configs(self) -> anyhow::Result<()> {
         let guilds = self.http.current_user_guilds().await?.model().await?;
-        let configs = self.server_configs.lock().await.clone();
         for guild in guilds {
-            if configs.get(&guild.id).is_none() {
-                _ = self
-                    .server_configs
-                    .lock()
-                    .await
-                    .insert(guild.id, ServerConfig::new());
-            }
+            guild_joined(&self, guild.id).await?;
         }
         Ok(())
     }
 
-    async fn write_configs_to_file(&self) -> anyhow::Result<()> {
-        let configs = self.server_configs.lock().await.clone();
-        for (guild, config) in configs.iter() {
-            let data = bincode::serde::encode_to_vec(config, bincode::config::standard())?;
-        }
+    async fn register_commands(&self) -> anyhow::Result<()> {
+        let application_id = self
+            .http
+            .current_user_application()
+            .await?
+            .model()
+            .await?
+            .id;
+        let commands = slash::application_commands(&self.root_cmd);
+        self.http
+            .interaction(application_id)
+            .set_global_commands(&commands)
+            .await?;
+        tracing::info!("Registered {} slash commands", commands.len());
         Ok(())
     }
 
     async fn read_configs_from_file(&self) -> anyhow::Result<()> {
+        let loaded = self.storage.load_configs().await?;
+        tracing::info!("Loaded {} server configs", loaded.len());
+        self.server_configs.lock().await.extend(loaded);
         Ok(())
     }
 
@@ -99,8 +293,8 @@ impl Handler for State {
         loop {
             let guilds = get_empty_vcs(Arc::clone(&self)).await;
             for guild in guilds {
-                tokio::spawn((async move |guild, state| {
-                    tokio::time::sleep(std::time::Duration::SECOND * 60).await;
+                tokio::spawn((async move |guild, state: State| {
+                    tokio::time::sleep(state.bot.idle_timeout).await;
                     let guilds = get_empty_vcs(Arc::clone(&state)).await;
                     let this_guild: Vec<_> = guilds.iter().filter(|x| **x == guild).collect();
 
@@ -115,9 +309,42 @@ impl Handler for State {
         }
     }
 
+    async fn purge_departed_guilds(self) -> anyhow::Result<()> {
+        loop {
+            sleep(PURGE_INTERVAL).await;
+            let expired = self
+                .server_configs
+                .lock()
+                .await
+                .iter()
+                .filter(|(_, config)| config.expired(self.bot.config_grace))
+                .map(|(guild, _)| *guild)
+                .collect::<Vec<_>>();
+            for guild in expired {
+                let _saving = self.lock_config(guild).await;
+                let mut configs = self.server_configs.lock().await;
+                // The bot may have been added back while waiting for the lock.
+                if !configs
+                    .get(&guild)
+                    .is_some_and(|config| config.expired(self.bot.config_grace))
+                {
+                    continue;
+                }
+                configs.remove(&guild);
+                drop(configs);
+                // It is gone from memory either way, a later start tries deleting it again.
+                if let Err(why) = self.storage.delete_config(guild).await {
+                    tracing::error!("could not delete the config of {guild}: {why:?}");
+                    continue;
+                }
+                tracing::info!("Deleted the config of {guild}, which the bot was removed from");
+            }
+        }
+    }
+
     async fn check_done_vcs(self) -> anyhow::Result<()> {
         loop {
-            sleep(Duration::new(1, 0)).await;
+            sleep(self.bot.poll_interval).await;
             let mut guilds = vec![];
             let queues = self.vcs.lock().await.clone();
             for i in queues.iter() {
@@ -140,35 +367,202 @@ impl Handler for State {
         }
     }
 
+    /// Runs the command in a text message, if there is one. Called again when a message which
+    /// recently invoked a command is edited.
+    async fn handle_message(self, msg: &Message) -> anyhow::Result<()> {
+        let mut txt_cmd = TextCommand::new(&msg.content);
+        if txt_cmd.clone().collect::<Vec<String>>().is_empty() {
+            return Ok(());
+        }
+        let config = self.config(msg.guild_id).await;
+        let pfx = config.prefix(&self.bot.default_prefix);
+
+        let prefix_commmand = if mentions_bot(&self.cache, txt_cmd.first()) {
+            _ = txt_cmd.next();
+            txt_cmd.next()
+        } else {
+            let stripped = config
+                .strip_prefix(txt_cmd.first(), &self.bot.default_prefix)
+                .map(String::from);
+            if stripped.is_some() {
+                _ = txt_cmd.next();
+            }
+            stripped
+        };
+        let Some(mut prefix_commmand) = prefix_commmand.filter(|c| !c.is_empty()) else {
+            return Ok(());
+        };
+        self.responses.invoked(msg.id, &msg.content).await;
+        if config.ignores_prefix_case() {
+            prefix_commmand = prefix_commmand.to_lowercase();
+        }
+
+        if self.root_cmd.find_command(&prefix_commmand).is_none() {
+            if let Some(expansion) = config.alias(&prefix_commmand) {
+                let rest = txt_cmd.rest().unwrap_or_default();
+                txt_cmd = TextCommand::new(&format!("{expansion} {rest}"));
+                prefix_commmand = txt_cmd.next().unwrap_or_default();
+            }
+        }
+
+        if let Some((subcommand, parents)) =
+            self.root_cmd
+                .resolve(&prefix_commmand, &mut txt_cmd, config.ignores_prefix_case())
+        {
+            let ctx = Context::from_message(Arc::clone(&self), msg);
+            let Some(func) = subcommand.function else {
+                let group = format!("{pfx}{parents}{}", subcommand.name);
+                let content = match txt_cmd.peek() {
+                    Some(word) if !subcommand.close_subcommands(word).is_empty() => format!(
+                        "There is no command called `{group} {word}`. Did you mean `{group} {}`?",
+                        subcommand
+                            .close_subcommands(word)
+                            .join(&format!("`, `{group} "))
+                    ),
+                    _ => describe(&subcommand, &parents, &pfx),
+                };
+                return ctx.reply(&content).await;
+            };
+
+            if !check_guild(&ctx, &subcommand, &parents).await?
+                || !check_rules(&ctx, &subcommand, &parents).await?
+                || !check_permissions(&ctx, &subcommand).await?
+            {
+                return Ok(());
+            }
+
+            let command_with_data = match CommandWithData::new(txt_cmd, (*subcommand).clone()) {
+                Ok(c) => c,
+                Err(why) => {
+                    let Some(why) = why.downcast_ref::<ParseError>() else {
+                        return Err(why);
+                    };
+                    return ctx
+                        .reply(&format!(
+                            "{why}\nUsage: `{}`",
+                            subcommand.usage(&format!("{pfx}{parents}"))
+                        ))
+                        .await;
+                }
+            };
+            if !check_cooldown(&ctx, &subcommand, &parents).await? {
+                return Ok(());
+            }
+            let path = format!("{parents}{}", subcommand.name);
+            _ = tokio::spawn(async move {
+                let command = func(Arc::clone(&self), ctx.clone(), command_with_data);
+                error::run(&ctx, &path, command).await;
+            });
+        } else if config.suggests_commands() {
+            let names = self
+                .root_cmd
+                .subcommands
+                .iter()
+                .flatten()
+                .flat_map(Command::names)
+                .chain(config.aliases.iter().flatten().map(|(n, _)| n.as_str()));
+            let suggestions = suggest(&prefix_commmand, names);
+            if !suggestions.is_empty() {
+                Context::from_message(Arc::clone(&self), msg)
+                    .reply(&format!(
+                        "There is no command called `{pfx}{prefix_commmand}`. Did you mean `{pfx}{}`?",
+                        suggestions.join(&format!("`, `{pfx}"))
+                    ))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
     async fn handle_event(self, event: Event) -> anyhow::Result<()> {
         self.songbird.process(&event).await;
         self.cache.update(&event);
+        self.standby.process(&event);
 
         match event {
-            //TODO: unhardcode the prefix
-            Event::MessageCreate(msg) => {
-                let mut txt_cmd = TextCommand::new(&msg.content);
-                if txt_cmd.clone().collect::<Vec<String>>().is_empty() {
+            Event::MessageCreate(msg) => self.handle_message(&msg).await?,
+            Event::MessageUpdate(msg) => {
+                if self.responses.edited(msg.id, &msg.content).await {
+                    self.handle_message(&msg).await?;
+                }
+            }
+            Event::Ready(ready) => {
+                // Servers the bot was removed from while it was offline, or disconnected.
+                let departed = self
+                    .server_configs
+                    .lock()
+                    .await
+                    .iter()
+                    .filter(|(guild, config)| {
+                        config.left_at().is_none() && !ready.guilds.iter().any(|g| g.id == **guild)
+                    })
+                    .map(|(guild, _)| *guild)
+                    .collect::<Vec<_>>();
+                for guild in departed {
+                    guild_left(&self, guild).await?;
+                }
+            }
+            Event::GuildCreate(guild) => guild_joined(&self, guild.id()).await?,
+            // Only in case the server's config was somehow missed when it was created.
+            Event::GuildUpdate(guild) => {
+                let missing = !self.server_configs.lock().await.contains_key(&guild.id);
+                if missing {
+                    guild_joined(&self, guild.id).await?;
+                }
+            }
+            Event::GuildDelete(guild) => {
+                // Only an outage, the bot is still in the server.
+                if guild.unavailable != Some(true) {
+                    guild_left(&self, guild.id).await?;
+                }
+            }
+            Event::InteractionCreate(interaction) => {
+                let Some(InteractionData::ApplicationCommand(data)) = &interaction.data else {
+                    return Ok(());
+                };
+                let Some((subcommand, parents, options)) = slash::resolve(&self.root_cmd, data)
+                else {
+                    return Ok(());
+                };
+                let Some(func) = subcommand.function else {
+                    return Ok(());
+                };
+                let Some(ctx) = Context::from_interaction(Arc::clone(&self), &interaction) else {
+                    return Ok(());
+                };
+
+                if !check_guild(&ctx, &subcommand, &parents).await?
+                    || !check_rules(&ctx, &subcommand, &parents).await?
+                    || !check_permissions(&ctx, &subcommand).await?
+                {
                     return Ok(());
                 }
-                let configs = self.server_configs.lock().await.clone();
-                let pfx = configs.get(&msg.guild_id.unwrap()).unwrap().prefix();
-
-                if pfx.is_prefix_of(txt_cmd.first()) {
-                    if let Some(prefix_commmand) = txt_cmd.first().strip_prefix(&pfx) {
-                        if let Some(subcommand) = self.root_cmd.find_command(prefix_commmand) {
-                            if let Some(func) = subcommand.function {
-                                _ = txt_cmd.next();
-                                let command_with_data = CommandWithData::new(txt_cmd, *subcommand)?;
-                                _ = tokio::spawn(async move {
-                                    (func)(Arc::clone(&self), *msg.clone(), command_with_data)
-                                        .await
-                                        .unwrap();
-                                });
-                            }
+
+                let command_with_data =
+                    match CommandWithData::from_options((*subcommand).clone(), &options) {
+                        Ok(c) => c,
+                        Err(why) => {
+                            return ctx
+                                .reply(&format!(
+                                    "{why}\nUsage: `{}`",
+                                    subcommand.usage(&format!("/{parents}"))
+                                ))
+                                .await;
                         }
-                    }
+                    };
+
+                if !check_cooldown(&ctx, &subcommand, &parents).await? {
+                    return Ok(());
                 }
+
+                let path = format!("{parents}{}", subcommand.name);
+                _ = tokio::spawn(async move {
+                    let command = func(Arc::clone(&self), ctx.clone(), command_with_data);
+                    error::run(&ctx, &path, command).await;
+                    if let Err(why) = ctx.finish().await {
+                        tracing::warn!("could not finish responding to `{path}`: {why}");
+                    }
+                });
             }
             Event::ReactionAdd(reaction) => match &reaction.emoji {
                 EmojiReactionType::Custom {
@@ -195,14 +589,36 @@ impl Handler for State {
 }
 
 impl StateRef<'static> {
+    /// A copy of `guild`'s config, or the defaults in DMs and for guilds without one.
+    pub async fn config(&self, guild: Option<Id<GuildMarker>>) -> ServerConfig {
+        let configs = self.server_configs.lock().await;
+        guild
+            .and_then(|guild| configs.get(&guild))
+            .cloned()
+            .unwrap_or_else(ServerConfig::new)
+    }
+
+    /// Waits for any change to `guild`'s config to be saved. Hold the guard from before changing
+    /// the config until it is saved, so saves land in the order the changes were made.
+    pub async fn lock_config(&self, guild: Id<GuildMarker>) -> OwnedMutexGuard<()> {
+        let lock = Arc::clone(self.config_saves.lock().await.entry(guild).or_default());
+        lock.lock_owned().await
+    }
+
     pub const fn new(
         root_cmd: Command,
         http: HttpClient,
         songbird: Songbird,
         vcs: Mutex<HashMap<Id<GuildMarker>, Arc<Mutex<Queue<'static>>>>>,
         server_configs: Mutex<HashMap<Id<GuildMarker>, ServerConfig>>,
+        config_saves: Mutex<HashMap<Id<GuildMarker>, Arc<Mutex<()>>>>,
         client: Client,
         cache: InMemoryCache,
+        storage: Backend,
+        cooldowns: Cooldowns,
+        responses: Responses,
+        standby: Standby,
+        bot: BotConfig,
     ) -> Self {
         StateRef {
             root_cmd,
@@ -210,8 +626,14 @@ impl StateRef<'static> {
             songbird,
             vcs,
             server_configs,
+            config_saves,
             client,
             cache,
+            storage,
+            cooldowns,
+            responses,
+            standby,
+            bot,
         }
     }
 }
@@ -0,0 +1,155 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    random::{DefaultRandomSource, Random},
+};
+
+use anyhow::Context;
+use tokio::{fs, io::AsyncWriteExt};
+use twilight_model::id::{marker::GuildMarker, Id};
+
+use crate::config::{schema, servers::ServerConfig};
+
+use super::Storage;
+
+const CONFIG_EXTENSION: &str = "bin";
+const TEMP_EXTENSION: &str = "tmp";
+const CORRUPT_EXTENSION: &str = "corrupt";
+
+/// Stores each guild's config as its own file under `<data dir>/configs`.
+pub struct FileStorage {
+    dir: PathBuf,
+}
+
+impl FileStorage {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            dir: data_dir.join("configs"),
+        }
+    }
+
+    fn config_path(&self, guild: Id<GuildMarker>) -> PathBuf {
+        self.dir.join(format!("{guild}.{CONFIG_EXTENSION}"))
+    }
+}
+
+/// Writes `data` to a new file at `path` and waits for it to reach the disk.
+async fn write_synced(path: &Path, data: &[u8]) -> anyhow::Result<()> {
+    let mut file = fs::File::create_new(path).await?;
+    file.write_all(data).await?;
+    file.sync_all().await?;
+    Ok(())
+}
+
+impl Storage for FileStorage {
+    /// The data is first written and synced to a temporary file which is then renamed over the
+    /// old config, so a crash halfway through a write never leaves a truncated config behind.
+    /// Each save gets its own temporary file, so concurrent saves never write into the same one.
+    async fn save_config(
+        &self,
+        guild: Id<GuildMarker>,
+        config: &ServerConfig,
+    ) -> anyhow::Result<()> {
+        fs::create_dir_all(&self.dir)
+            .await
+            .with_context(|| format!("could not create config directory {}", self.dir.display()))?;
+
+        let data = schema::encode(config)?;
+
+        let path = self.config_path(guild);
+        let tmp = self.dir.join(format!(
+            "{guild}.{:016x}.{TEMP_EXTENSION}",
+            u64::random(&mut DefaultRandomSource)
+        ));
+
+        if let Err(why) = write_synced(&tmp, &data).await {
+            let _ = fs::remove_file(&tmp).await;
+            return Err(why.context(format!("could not write {}", tmp.display())));
+        }
+        fs::rename(&tmp, &path)
+            .await
+            .with_context(|| format!("could not move {} to {}", tmp.display(), path.display()))?;
+
+        // The rename only survives a crash once the directory entry itself is on disk.
+        fs::File::open(&self.dir)
+            .await?
+            .sync_all()
+            .await
+            .with_context(|| format!("could not sync {}", self.dir.display()))?;
+
+        Ok(())
+    }
+
+    /// Files which cannot be decoded are renamed with a `.corrupt` extension.
+    async fn load_configs(&self) -> anyhow::Result<HashMap<Id<GuildMarker>, ServerConfig>> {
+        let mut configs = HashMap::new();
+
+        if !fs::try_exists(&self.dir).await? {
+            return Ok(configs);
+        }
+
+        let mut entries = fs::read_dir(&self.dir)
+            .await
+            .with_context(|| format!("could not read config directory {}", self.dir.display()))?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            match path.extension().and_then(|e| e.to_str()) {
+                Some(CONFIG_EXTENSION) => {}
+                Some(TEMP_EXTENSION) => {
+                    tracing::warn!(
+                        "found partially written config {}, removing it",
+                        path.display()
+                    );
+                    fs::remove_file(&path)
+                        .await
+                        .with_context(|| format!("could not remove {}", path.display()))?;
+                    continue;
+                }
+                _ => continue,
+            }
+
+            let Some(guild) = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.parse::<Id<GuildMarker>>().ok())
+            else {
+                tracing::warn!(
+                    "{} is not named after a guild id, ignoring it",
+                    path.display()
+                );
+                continue;
+            };
+
+            let data = fs::read(&path)
+                .await
+                .with_context(|| format!("could not read {}", path.display()))?;
+
+            match schema::decode(&data) {
+                Ok(config) => {
+                    configs.insert(guild, config);
+                }
+                Err(why) => {
+                    let corrupt = path.with_extension(CORRUPT_EXTENSION);
+                    tracing::error!(
+                        "config for guild {guild} could not be decoded ({why}), moved it to {}",
+                        corrupt.display()
+                    );
+                    fs::rename(&path, &corrupt).await?;
+                }
+            }
+        }
+
+        Ok(configs)
+    }
+
+    async fn delete_config(&self, guild: Id<GuildMarker>) -> anyhow::Result<()> {
+        let path = self.config_path(guild);
+        if fs::try_exists(&path).await? {
+            fs::remove_file(&path)
+                .await
+                .with_context(|| format!("could not remove {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
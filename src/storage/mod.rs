@@ -0,0 +1,70 @@
+mod files;
+mod sqlite;
+
+use std::{collections::HashMap, path::Path};
+
+use twilight_model::id::{marker::GuildMarker, Id};
+
+use crate::config::servers::ServerConfig;
+
+pub use files::FileStorage;
+pub use sqlite::SqliteStorage;
+
+/// `Storage` is anything which can persist the bot's per-guild data between restarts.
+pub trait Storage {
+    async fn save_config(
+        &self,
+        guild: Id<GuildMarker>,
+        config: &ServerConfig,
+    ) -> anyhow::Result<()>;
+    /// Every stored config. Configs which cannot be read are reported and set aside, rather than
+    /// being overwritten by a fresh default config on the next write.
+    async fn load_configs(&self) -> anyhow::Result<HashMap<Id<GuildMarker>, ServerConfig>>;
+    async fn delete_config(&self, guild: Id<GuildMarker>) -> anyhow::Result<()>;
+}
+
+/// The storage backend chosen at startup.
+pub enum Backend {
+    Files(FileStorage),
+    Sqlite(SqliteStorage),
+}
+
+impl Backend {
+    /// Opens the backend named by `kind` (`files` or `sqlite`) inside `data_dir`.
+    pub fn open(kind: &str, data_dir: &Path) -> anyhow::Result<Self> {
+        Ok(match kind {
+            "files" => Self::Files(FileStorage::new(data_dir)),
+            "sqlite" => Self::Sqlite(SqliteStorage::open(data_dir)?),
+            other => {
+                anyhow::bail!("unknown storage backend `{other}`, expected `files` or `sqlite`")
+            }
+        })
+    }
+}
+
+impl Storage for Backend {
+    async fn save_config(
+        &self,
+        guild: Id<GuildMarker>,
+        config: &ServerConfig,
+    ) -> anyhow::Result<()> {
+        match self {
+            Self::Files(s) => s.save_config(guild, config).await,
+            Self::Sqlite(s) => s.save_config(guild, config).await,
+        }
+    }
+
+    async fn load_configs(&self) -> anyhow::Result<HashMap<Id<GuildMarker>, ServerConfig>> {
+        match self {
+            Self::Files(s) => s.load_configs().await,
+            Self::Sqlite(s) => s.load_configs().await,
+        }
+    }
+
+    async fn delete_config(&self, guild: Id<GuildMarker>) -> anyhow::Result<()> {
+        match self {
+            Self::Files(s) => s.delete_config(guild).await,
+            Self::Sqlite(s) => s.delete_config(guild).await,
+        }
+    }
+}
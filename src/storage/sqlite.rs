@@ -0,0 +1,394 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Context;
+use rusqlite::{params, Connection, OptionalExtension};
+use twilight_model::id::{marker::GuildMarker, Id};
+
+use crate::config::{portable::PortableConfig, schema, servers::ServerConfig};
+
+use super::Storage;
+
+const DATABASE_FILE: &str = "cta.sqlite3";
+
+/// The version of the row layout below, bumped when a change to it needs existing rows migrated.
+const FORMAT_VERSION: u32 = 1;
+
+const CREATE_TABLES: &str = "
+    CREATE TABLE IF NOT EXISTS server_configs (
+        guild_id INTEGER PRIMARY KEY,
+        format_version INTEGER NOT NULL,
+        settings TEXT NOT NULL,
+        reminders TEXT,
+        left_at INTEGER,
+        updated_at INTEGER NOT NULL DEFAULT (unixepoch())
+    );
+    CREATE TABLE IF NOT EXISTS corrupt_server_configs (
+        guild_id INTEGER NOT NULL,
+        format_version INTEGER NOT NULL,
+        settings TEXT NOT NULL,
+        reminders TEXT,
+        left_at INTEGER,
+        updated_at INTEGER NOT NULL
+    );";
+
+/// Stores every guild's config as a row in an embedded sqlite database under the data dir.
+/// The settings are kept as JSON in the same layout `config export` uses, with reminders as JSON
+/// and the time the bot left the guild in columns of their own, so the database can be queried
+/// with `json_extract` and friends.
+pub struct SqliteStorage {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStorage {
+    pub fn open(data_dir: &Path) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(data_dir)
+            .with_context(|| format!("could not create data directory {}", data_dir.display()))?;
+
+        let path = data_dir.join(DATABASE_FILE);
+        let mut conn = Connection::open(&path)
+            .with_context(|| format!("could not open database {}", path.display()))?;
+
+        conn.execute_batch("PRAGMA journal_mode = WAL;")?;
+        migrate_bincode(&mut conn)?;
+        conn.execute_batch(CREATE_TABLES)?;
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// Runs a closure against the connection on the blocking thread pool.
+    async fn with_conn<T, F>(&self, f: F) -> anyhow::Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> anyhow::Result<T> + Send + 'static,
+    {
+        let conn = Arc::clone(&self.conn);
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn
+                .lock()
+                .map_err(|_| anyhow::anyhow!("database connection was poisoned"))?;
+            f(&mut conn)
+        })
+        .await?
+    }
+}
+
+/// Writes `config` into `guild`'s row, replacing what was there.
+fn write(conn: &Connection, guild: u64, config: &ServerConfig) -> anyhow::Result<()> {
+    let settings = serde_json::to_string(&PortableConfig::from(config))?;
+    let reminders = config
+        .reminders
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()?;
+    conn.execute(
+        "INSERT INTO server_configs (guild_id, format_version, settings, reminders, left_at)
+        VALUES (?1, ?2, ?3, ?4, ?5)
+        ON CONFLICT (guild_id) DO UPDATE SET
+            format_version = excluded.format_version,
+            settings = excluded.settings,
+            reminders = excluded.reminders,
+            left_at = excluded.left_at,
+            updated_at = unixepoch()",
+        params![guild, FORMAT_VERSION, settings, reminders, config.left_at()],
+    )?;
+    Ok(())
+}
+
+/// Puts a config back together from the columns of its row.
+fn read(
+    version: u32,
+    settings: &str,
+    reminders: Option<&str>,
+    left_at: Option<u64>,
+) -> anyhow::Result<ServerConfig> {
+    if version > FORMAT_VERSION {
+        anyhow::bail!(
+            "config has format version {version}, but this build only understands up to {FORMAT_VERSION}"
+        );
+    }
+    let mut config = ServerConfig::new();
+    serde_json::from_str::<PortableConfig>(settings)?.apply(&mut config);
+    config.reminders = reminders.map(serde_json::from_str).transpose()?;
+    config.set_left_at(left_at);
+    Ok(config)
+}
+
+/// Moves configs stored by earlier builds, as versioned bincode blobs in a `data` column, into
+/// the current layout. Blobs which cannot be decoded are left behind in `bincode_server_configs`.
+fn migrate_bincode(conn: &mut Connection) -> anyhow::Result<()> {
+    let legacy = conn
+        .query_row(
+            "SELECT 1 FROM pragma_table_info('server_configs') WHERE name = 'data'",
+            [],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some();
+    if !legacy {
+        return Ok(());
+    }
+
+    let tx = conn.transaction()?;
+    tx.execute_batch(
+        "ALTER TABLE server_configs RENAME TO bincode_server_configs;
+        ALTER TABLE corrupt_server_configs RENAME TO bincode_corrupt_server_configs;",
+    )?;
+    tx.execute_batch(CREATE_TABLES)?;
+
+    let rows = tx
+        .prepare("SELECT guild_id, data FROM bincode_server_configs")?
+        .query_map([], |row| {
+            Ok((row.get::<_, u64>(0)?, row.get::<_, Vec<u8>>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut left_behind = 0;
+    for (guild, data) in rows {
+        match schema::decode(&data) {
+            Ok(config) => {
+                write(&tx, guild, &config)?;
+                tx.execute(
+                    "DELETE FROM bincode_server_configs WHERE guild_id = ?1",
+                    params![guild],
+                )?;
+            }
+            Err(why) => {
+                tracing::error!(
+                    "config for guild {guild} could not be decoded ({why}), left it in bincode_server_configs"
+                );
+                left_behind += 1;
+            }
+        }
+    }
+    if left_behind == 0 {
+        tx.execute_batch("DROP TABLE bincode_server_configs;")?;
+    }
+    let corrupt = tx.query_row(
+        "SELECT count(*) FROM bincode_corrupt_server_configs",
+        [],
+        |row| row.get::<_, u64>(0),
+    )?;
+    if corrupt == 0 {
+        tx.execute_batch("DROP TABLE bincode_corrupt_server_configs;")?;
+    }
+    tx.commit()?;
+    tracing::info!("Moved server configs out of their bincode encoding");
+    Ok(())
+}
+
+impl Storage for SqliteStorage {
+    async fn save_config(
+        &self,
+        guild: Id<GuildMarker>,
+        config: &ServerConfig,
+    ) -> anyhow::Result<()> {
+        let config = config.clone();
+        self.with_conn(move |conn| write(conn, guild.get(), &config))
+            .await
+    }
+
+    /// Rows which cannot be read are moved to `corrupt_server_configs`.
+    async fn load_configs(&self) -> anyhow::Result<HashMap<Id<GuildMarker>, ServerConfig>> {
+        let rows = self
+            .with_conn(|conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT guild_id, format_version, settings, reminders, left_at
+                    FROM server_configs",
+                )?;
+                let rows = stmt
+                    .query_map([], |row| {
+                        Ok((
+                            row.get::<_, u64>(0)?,
+                            row.get::<_, u32>(1)?,
+                            row.get::<_, String>(2)?,
+                            row.get::<_, Option<String>>(3)?,
+                            row.get::<_, Option<u64>>(4)?,
+                        ))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(rows)
+            })
+            .await?;
+
+        let mut configs = HashMap::new();
+        for (guild, version, settings, reminders, left_at) in rows {
+            let Some(guild) = Id::new_checked(guild) else {
+                tracing::warn!("database contains a config for guild id 0, ignoring it");
+                continue;
+            };
+            match read(version, &settings, reminders.as_deref(), left_at) {
+                Ok(config) => {
+                    configs.insert(guild, config);
+                }
+                Err(why) => {
+                    tracing::error!(
+                        "config for guild {guild} could not be read ({why}), moved it to corrupt_server_configs"
+                    );
+                    self.with_conn(move |conn| {
+                        let tx = conn.transaction()?;
+                        tx.execute(
+                            "INSERT INTO corrupt_server_configs
+                            SELECT guild_id, format_version, settings, reminders, left_at, updated_at
+                            FROM server_configs WHERE guild_id = ?1",
+                            params![guild.get()],
+                        )?;
+                        tx.execute(
+                            "DELETE FROM server_configs WHERE guild_id = ?1",
+                            params![guild.get()],
+                        )?;
+                        tx.commit()?;
+                        Ok(())
+                    })
+                    .await?;
+                }
+            }
+        }
+
+        Ok(configs)
+    }
+
+    async fn delete_config(&self, guild: Id<GuildMarker>) -> anyhow::Result<()> {
+        self.with_conn(move |conn| {
+            conn.execute(
+                "DELETE FROM server_configs WHERE guild_id = ?1",
+                params![guild.get()],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        path::PathBuf,
+        random::{DefaultRandomSource, Random},
+        time::Duration,
+    };
+
+    use rusqlite::{params, Connection};
+    use twilight_model::id::Id;
+
+    use super::{SqliteStorage, DATABASE_FILE};
+    use crate::{
+        config::{
+            schema,
+            servers::{Reminder, ServerConfig},
+        },
+        storage::Storage,
+    };
+
+    /// A data dir of its own for each test, removed when dropped.
+    struct DataDir(PathBuf);
+
+    impl DataDir {
+        fn new() -> Self {
+            let name = format!("cta-test-{:016x}", u64::random(&mut DefaultRandomSource));
+            Self(std::env::temp_dir().join(name))
+        }
+    }
+
+    impl Drop for DataDir {
+        fn drop(&mut self) {
+            _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn config() -> ServerConfig {
+        let mut config = ServerConfig::new();
+        config.set_prefix("!");
+        config.reaction_roles = Some(HashMap::from([((Id::new(1), Id::new(2)), Id::new(3))]));
+        config.reminders = Some(HashMap::from([(
+            Id::new(4),
+            vec![Reminder {
+                begin: Duration::from_secs(5),
+                end: Duration::from_secs(6),
+                message: String::from("stretch"),
+            }],
+        )]));
+        config.archive();
+        config
+    }
+
+    /// Whether `a` and `b` hold the same settings, reminders and departure time.
+    fn same(a: &ServerConfig, b: &ServerConfig) -> bool {
+        schema::encode(a).unwrap() == schema::encode(b).unwrap()
+    }
+
+    #[tokio::test]
+    async fn stores_configs_as_json() {
+        let dir = DataDir::new();
+        let storage = SqliteStorage::open(&dir.0).unwrap();
+        storage.save_config(Id::new(7), &config()).await.unwrap();
+
+        let loaded = storage.load_configs().await.unwrap();
+        assert!(same(&loaded[&Id::new(7)], &config()));
+
+        let conn = Connection::open(dir.0.join(DATABASE_FILE)).unwrap();
+        let prefixes = conn
+            .query_row(
+                "SELECT settings ->> '$.prefixes[0]' FROM server_configs WHERE guild_id = 7",
+                [],
+                |row| row.get::<_, String>(0),
+            )
+            .unwrap();
+        assert_eq!(prefixes, "!");
+    }
+
+    #[tokio::test]
+    async fn moves_unreadable_rows_aside() {
+        let dir = DataDir::new();
+        let storage = SqliteStorage::open(&dir.0).unwrap();
+        storage.save_config(Id::new(7), &config()).await.unwrap();
+        let conn = Connection::open(dir.0.join(DATABASE_FILE)).unwrap();
+        conn.execute("UPDATE server_configs SET settings = '{'", [])
+            .unwrap();
+
+        assert!(storage.load_configs().await.unwrap().is_empty());
+        let corrupt = conn
+            .query_row("SELECT count(*) FROM corrupt_server_configs", [], |row| {
+                row.get::<_, u32>(0)
+            })
+            .unwrap();
+        assert_eq!(corrupt, 1);
+    }
+
+    #[tokio::test]
+    async fn migrates_bincode_rows() {
+        let dir = DataDir::new();
+        std::fs::create_dir_all(&dir.0).unwrap();
+        let conn = Connection::open(dir.0.join(DATABASE_FILE)).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE server_configs (
+                guild_id INTEGER PRIMARY KEY,
+                schema_version INTEGER NOT NULL,
+                data BLOB NOT NULL,
+                updated_at INTEGER NOT NULL DEFAULT (unixepoch())
+            );
+            CREATE TABLE corrupt_server_configs (
+                guild_id INTEGER NOT NULL,
+                schema_version INTEGER NOT NULL,
+                data BLOB NOT NULL,
+                updated_at INTEGER NOT NULL
+            );",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO server_configs (guild_id, schema_version, data) VALUES (7, ?1, ?2)",
+            params![schema::CURRENT_VERSION, schema::encode(&config()).unwrap()],
+        )
+        .unwrap();
+        drop(conn);
+
+        let storage = SqliteStorage::open(&dir.0).unwrap();
+        let loaded = storage.load_configs().await.unwrap();
+        assert!(same(&loaded[&Id::new(7)], &config()));
+    }
+}